@@ -0,0 +1,154 @@
+use crate::*;
+use crate::menu::{GameMode, GameState, GameSettings};
+use crate::car::{Car, AxleLayout, FrontWheel, WheelSuspension};
+use crate::car_spec::CarRoster;
+use crate::input::{CarInput, CarInputSet, InputSource};
+use crate::track_path::{ActiveTrackPath, TrackPath};
+use crate::world::spawn_car;
+
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AiConfig>()
+            .add_systems(OnEnter(GameState::InGame), queue_grid.run_if(not(in_state(GameMode::TimeTrial)))) // Time trials are run alone
+            .add_systems(OnExit(GameState::InGame), clear_grid)
+            .add_systems(Update, spawn_ai_drivers.run_if(resource_exists::<PendingGrid>))
+            .add_systems(FixedUpdate, ai_driver_system.in_set(CarInputSet).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// How many AI opponents join a session and how well they drive
+#[derive(Resource, Clone)]
+pub struct AiConfig {
+    pub opponents: usize,
+    pub skill: f32, // 0.0 cautious to 1.0 on the limit, for the car at the front of the grid
+    pub skill_spread: f32, // Each car further back the grid is this much less skilled
+    pub car: Option<String>, // Roster id the AI drives - the player's choice when None
+    pub grid_spacing: f32, // Metres between grid slots along the track path
+    pub look_ahead: f32, // Pursuit distance (m) at a standstill
+    pub look_ahead_time: f32, // Extra pursuit distance per m/s of speed
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            opponents: 3,
+            skill: 0.9,
+            skill_spread: 0.1,
+            car: None,
+            grid_spacing: 6.0,
+            look_ahead: 5.0,
+            look_ahead_time: 0.35,
+        }
+    }
+}
+
+/// Drives a car whose `InputSource` is `Ai` along the active track path
+#[derive(Component, Clone, Copy, Debug)]
+pub struct AiDriver {
+    pub skill: f32,
+    pub progress: f32, // Distance along the track path at the last tick
+    pub target_speed: f32, // What the speed planner asked for last tick
+}
+
+/// Marks a session whose grid hasn't been spawned yet - it waits for the track path to load
+#[derive(Resource)]
+struct PendingGrid;
+
+fn queue_grid(mut commands: Commands) {
+    commands.insert_resource(PendingGrid);
+}
+
+fn clear_grid(mut commands: Commands) {
+    commands.remove_resource::<PendingGrid>();
+}
+
+fn spawn_ai_drivers(
+    mut commands: Commands,
+    config: Res<AiConfig>,
+    active_path: Res<ActiveTrackPath>,
+    paths: Res<Assets<TrackPath>>,
+    roster: Res<CarRoster>,
+    settings: Res<GameSettings>,
+) {
+    let Some(path) = paths.get(&active_path.0) else {
+        return; // Still loading
+    };
+    commands.remove_resource::<PendingGrid>();
+    let car_id = config.car.as_deref().unwrap_or(&settings.car);
+
+    let mut spawned = 0;
+    for slot in 0..config.opponents {
+        // Two abreast across the road, staggered back from the start line
+        let sample = path.sample(path.point_distance(path.start_line) - (slot + 1) as f32 * config.grid_spacing);
+        let side = if slot % 2 == 0 { 0.25 } else { -0.25 } * sample.width;
+        let position = sample.position + sample.right() * side + Vec3::Y * 0.7;
+        let transform = Transform::from_translation(position).looking_to(sample.direction, Vec3::Y);
+
+        let Some(car) = spawn_car(&mut commands, &roster, car_id, transform) else {
+            break; // Every slot uses the same car, so the rest would fail too
+        };
+        let skill = (config.skill - slot as f32 * config.skill_spread).clamp(0.0, 1.0);
+        commands.entity(car).insert((
+            InputSource::Ai,
+            AiDriver { skill, progress: sample.distance, target_speed: 0.0 },
+        ));
+        spawned += 1;
+    }
+    println!("Spawned {} AI drivers on {}", spawned, path.name);
+}
+
+fn ai_driver_system(
+    config: Res<AiConfig>,
+    active_path: Option<Res<ActiveTrackPath>>,
+    paths: Res<Assets<TrackPath>>,
+    mut car_query: Query<(Entity, &Transform, &Car, &InputSource, &mut AiDriver, &mut CarInput)>,
+    wheel_query: Query<(&WheelSuspension, Has<FrontWheel>)>,
+) {
+    let Some(path) = active_path.and_then(|active_path| paths.get(&active_path.0)) else {
+        return;
+    };
+
+    for (entity, transform, car, source, mut driver, mut input) in car_query.iter_mut() {
+        if *source != InputSource::Ai {
+            continue;
+        }
+        let position = transform.translation;
+        let speed = car.speed.max(0.0);
+        driver.progress = path.progress(position);
+
+        // Pure pursuit - steer onto the arc that passes through a point further up the path
+        let look_ahead = config.look_ahead + speed * config.look_ahead_time;
+        let target = transform.rotation.inverse() * (path.point_at(driver.progress + look_ahead) - position);
+        let ahead = Vec2::new(target.x, -target.z); // Right, forward
+        let heading_error = ahead.x.atan2(ahead.y);
+        let layout = AxleLayout::of(wheel_query.iter().filter(|(wheel, _)| wheel.car == entity));
+        let wheel_angle = (2.0 * layout.wheelbase * heading_error.sin() / ahead.length().max(1.0)).atan();
+        let steer = (wheel_angle / car.steering_lock().max(f32::EPSILON)).clamp(-1.0, 1.0);
+
+        // Corner speeds from the path's curvature, capped by its target speeds - braking early enough for
+        // every corner in range. Better drivers lean on more of the grip for both
+        let grip = car.tire.peak_friction * 9.81 * (0.45 + 0.4 * driver.skill);
+        let braking = grip * 0.8;
+        let horizon = car.top_speed().powi(2) / (2.0 * braking);
+        let mut target_speed = car.top_speed();
+        let mut distance = 0.0;
+        while distance < horizon {
+            let sample = path.sample(driver.progress + distance);
+            let corner_speed = (grip / sample.curvature.abs().max(1e-4)).sqrt().min(sample.target_speed);
+            target_speed = target_speed.min((corner_speed.powi(2) + 2.0 * braking * distance).sqrt());
+            distance += 2.0;
+        }
+        driver.target_speed = target_speed;
+
+        let speed_error = target_speed - speed;
+        *input = CarInput {
+            throttle: (speed_error / 3.0).clamp(0.0, 1.0),
+            brake: (-speed_error / 4.0).clamp(0.0, 1.0),
+            steer,
+            boost: speed_error > 10.0 && driver.skill > 0.5, // Only the quick ones time the nitro
+            ..default()
+        };
+    }
+}
@@ -0,0 +1,104 @@
+use crate::*;
+use crate::menu::{GameState, GameSettings};
+use crate::input::{InputSource, CarInputSet};
+
+pub struct AssistsPlugin;
+
+impl Plugin for AssistsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, sync_player_assists.before(CarInputSet).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// How strongly a driver aid intervenes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AssistLevel {
+    Off,
+    #[default]
+    Low, // Only catches large slides - lets the driver play with the limit
+    High, // Steps in early and firmly
+}
+
+impl AssistLevel {
+    /// Next level when cycling through them in the settings menu
+    pub fn next(self) -> Self {
+        match self {
+            AssistLevel::Off => AssistLevel::Low,
+            AssistLevel::Low => AssistLevel::High,
+            AssistLevel::High => AssistLevel::Off,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AssistLevel::Off => "OFF",
+            AssistLevel::Low => "LOW",
+            AssistLevel::High => "HIGH",
+        }
+    }
+
+    /// Slip beyond which the aid steps in, `None` when switched off
+    fn threshold(self, low: f32, high: f32) -> Option<f32> {
+        match self {
+            AssistLevel::Off => None,
+            AssistLevel::Low => Some(low),
+            AssistLevel::High => Some(high),
+        }
+    }
+}
+
+/// Driver aids fitted to a car, plus which ones acted this frame for the HUD
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct DriverAssists {
+    pub abs: AssistLevel,
+    pub traction_control: AssistLevel,
+    pub stability_control: AssistLevel,
+    pub abs_active: bool,
+    pub traction_control_active: bool,
+    pub stability_control_active: bool,
+}
+
+impl DriverAssists {
+    /// ABS: share of the requested brake torque to keep on a wheel with this slip ratio
+    pub fn abs_brake_scale(&self, slip_ratio: f32) -> f32 {
+        match self.abs.threshold(0.25, 0.12) {
+            Some(threshold) if slip_ratio < -threshold => 0.3, // Release pressure so the wheel spins back up
+            _ => 1.0,
+        }
+    }
+
+    /// Traction control: share of the engine torque to keep on a driven wheel with this slip ratio
+    pub fn traction_scale(&self, slip_ratio: f32) -> f32 {
+        match self.traction_control.threshold(0.3, 0.15) {
+            Some(threshold) if slip_ratio > threshold => 0.3,
+            _ => 1.0,
+        }
+    }
+
+    /// Stability control: extra brake torque (Nm) for the outside front wheel while the tail slides out
+    pub fn stability_brake_torque(&self, drift_angle: f32, yaw_rate: f32) -> f32 {
+        // Sliding one way while rotating the other means the car is oversteering
+        let oversteering = drift_angle * yaw_rate < 0.0;
+        let Some(threshold) = self.stability_control.threshold(0.3, 0.15) else {
+            return 0.0;
+        };
+        if !oversteering || drift_angle.abs() <= threshold {
+            return 0.0;
+        }
+        if self.stability_control == AssistLevel::High { 1500.0 } else { 800.0 }
+    }
+}
+
+// Player cars follow the assist levels chosen in the settings menu
+fn sync_player_assists(
+    settings: Res<GameSettings>,
+    mut car_query: Query<(&mut DriverAssists, &InputSource)>,
+) {
+    for (mut assists, source) in car_query.iter_mut() {
+        if matches!(source, InputSource::Keyboard | InputSource::Gamepad) {
+            assists.abs = settings.abs;
+            assists.traction_control = settings.traction_control;
+            assists.stability_control = settings.stability_control;
+        }
+    }
+}
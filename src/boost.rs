@@ -0,0 +1,114 @@
+use crate::*;
+use crate::menu::GameState;
+use crate::car::Car;
+use crate::simulation::CarSimulationSet;
+use crate::recovery::CarRecovery;
+use crate::surface::SurfaceType;
+use bevy_rapier3d::prelude::*;
+
+pub struct BoostPlugin;
+
+impl Plugin for BoostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NearMissConfig>()
+            .add_event::<NearMiss>()
+            .add_systems(FixedUpdate, near_miss_system.in_set(CarSimulationSet).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// What counts as a near miss - the nitro reward itself is tuned per car in `Boost`
+#[derive(Resource, Clone)]
+pub struct NearMissConfig {
+    pub margin: f32, // How close (m) to the body an obstacle has to pass
+    pub min_speed: f32, // Slower passes don't count
+}
+
+impl Default for NearMissConfig {
+    fn default() -> Self {
+        Self {
+            margin: 1.5,
+            min_speed: 15.0, // ~55 km/h
+        }
+    }
+}
+
+/// Sent when a car skims past something without touching it, for HUD messages and scoring
+#[derive(Event, Clone, Copy, Debug)]
+pub struct NearMiss {
+    pub car: Entity,
+    pub obstacle: Entity,
+}
+
+/// Obstacles inside a car's near-miss zone, and whether passing each one still counts
+#[derive(Component, Default)]
+pub struct NearMissTracker {
+    pub nearby: Vec<(Entity, bool)>,
+}
+
+#[allow(clippy::type_complexity)]
+fn near_miss_system(
+    rapier_context: ReadRapierContext,
+    config: Res<NearMissConfig>,
+    ground_query: Query<(), With<SurfaceType>>,
+    mut car_query: Query<(Entity, &Transform, &Velocity, &Collider, &mut Car, &mut NearMissTracker, Option<&CarRecovery>)>,
+    mut near_miss_events: EventWriter<NearMiss>,
+) {
+    let Ok(rapier_context) = rapier_context.single() else {
+        return;
+    };
+
+    for (entity, transform, velocity, collider, mut car, mut tracker, recovery) in car_query.iter_mut() {
+        // Ghosted cars drive straight through things, which is no skill
+        if recovery.is_some_and(|recovery| recovery.ghost_timer > 0.0) {
+            tracker.nearby.clear();
+            continue;
+        }
+
+        // The body's bounding box grown by the margin on every side
+        let aabb = collider.raw.compute_local_aabb();
+        let half_extents = Vec3::from(aabb.half_extents()) + Vec3::splat(config.margin);
+        let center = transform.transform_point(Vec3::from(aabb.center().coords));
+        let zone = Collider::cuboid(half_extents.x, half_extents.y, half_extents.z);
+        let filter = QueryFilter::default()
+            .exclude_rigid_body(entity)
+            .exclude_sensors();
+
+        let mut inside = Vec::new();
+        rapier_context.intersections_with_shape(center, transform.rotation, &zone, filter, |hit| {
+            if !ground_query.contains(hit) {
+                inside.push(hit);
+            }
+            true
+        });
+
+        // Anything that touches the body no longer counts
+        for (obstacle, clean) in tracker.nearby.iter_mut() {
+            let touching = rapier_context
+                .contact_pair(entity, *obstacle)
+                .is_some_and(|pair| pair.has_any_active_contact());
+            *clean &= !touching;
+        }
+
+        // Obstacles leaving the zone untouched were near misses
+        let mut misses = Vec::new();
+        tracker.nearby.retain(|(obstacle, clean)| {
+            let still_inside = inside.contains(obstacle);
+            if !still_inside && *clean {
+                misses.push(*obstacle);
+            }
+            still_inside
+        });
+        for obstacle in misses {
+            car.boost_meter = (car.boost_meter + car.boost.near_miss_recharge).min(1.0);
+            near_miss_events.write(NearMiss { car: entity, obstacle });
+        }
+
+        // Only obstacles that come into the zone at speed can count
+        let fast = velocity.linvel.length() >= config.min_speed;
+        for obstacle in inside {
+            if !tracker.nearby.iter().any(|(tracked, _)| *tracked == obstacle) {
+                tracker.nearby.push((obstacle, fast));
+            }
+        }
+    }
+}
@@ -59,6 +59,7 @@ fn setup_camera_state(mut commands: Commands, settings: Res<GameSettings>) {
         Tonemapping::AcesFitted,
         // Bloom for realistic lighting
         Bloom::NATURAL,
+        SpatialListener::default(), // Hears the cars' tyres
         GameEntity,
     ));
 }
@@ -69,11 +70,12 @@ pub struct CameraState {
     stable_timer: f32,
 }
 
+#[allow(clippy::type_complexity)]
+#[allow(clippy::collapsible_if)] // Keeps the original nesting
 fn camera_follow_system(
     car_query: Query<(&Transform, &Car, &Velocity), (With<CameraTarget>, Without<Camera3d>)>,
     mut camera_query: Query<&mut Transform, (With<Camera3d>, Without<CameraTarget>)>,
     mut camera_state: ResMut<CameraState>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
 ) {
     if let Ok((car_transform, car, velocity)) = car_query.single() {
@@ -87,9 +89,8 @@ fn camera_follow_system(
             // Calculate velocity magnitude for camera responsiveness
             let velocity_magnitude = velocity.linvel.length();
             
-            // Determine if we're actively reversing based on input, not just speed
-            let is_actively_reversing = keyboard_input.pressed(KeyCode::ArrowDown) || 
-                                      keyboard_input.pressed(KeyCode::KeyS);
+            // Follow the drivetrain - holding brake only flips the camera once reverse actually engages
+            let is_actively_reversing = car.reversing;
             
             // Add stability timer to prevent rapid camera switching
             if camera_state.was_reversing != is_actively_reversing {
@@ -1,15 +1,54 @@
 use crate::*;
 use crate::menu::GameState;
+use crate::input::CarInput;
+use crate::simulation::CarSimulationSet;
+use crate::tire::TireSpec;
+use crate::surface::SurfaceType;
+use crate::assists::DriverAssists;
+use crate::damage::CarDamage;
 use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Component)]
 pub struct Car {
     pub speed: f32,
-    pub max_speed: f32,
-    pub acceleration: f32,
-    pub turn_speed: f32,
-    pub motor_force: f32,
+    pub max_speed: f32, // Reference top speed for camera and effects - no longer a hard cap
+    pub steering: Steering,
+    pub steer_angle: f32, // Road wheel angle (radians) on the car's centre line, positive steering right
     pub brake_force: f32,
+    pub braking: bool, // Brakes are applied this frame (either pedal, depending on direction)
+    pub reversing: bool, // Reverse gear is engaged
+    pub reverse_hold_timer: f32, // How long brake has been held while stopped
+    pub reverse_delay: f32, // Seconds of brake at a standstill before reverse engages
+    pub reverse_engage_speed: f32, // Below this speed (m/s) the car counts as stopped
+    pub max_reverse_speed: f32,
+    pub engine: Engine,
+    pub transmission: Transmission,
+    pub gear: usize, // Current forward gear, 1-based
+    pub rpm: f32,
+    pub shift_timer: f32, // Remaining torque cut while a gear change completes
+    pub aero: Aero,
+    pub suspension: Suspension,
+    pub tire: TireSpec,
+    pub front_grip: f32, // Per-axle grip multipliers - a weaker rear makes the car oversteer
+    pub rear_grip: f32,
+    pub handbrake_torque: f32, // Brake torque on each rear wheel while the handbrake is pulled (Nm)
+    pub weight_transfer: f32, // 0.0 ignores load transfer, 1.0 is physically scaled
+    pub center_of_mass_height: f32, // Above the contact patches, drives weight transfer
+    pub drift_angle: f32, // Radians between heading and travel direction, positive sliding left
+    pub front_slip: f32, // Sideways sliding speed at the front/rear contact patches (m/s)
+    pub rear_slip: f32,
+    pub drifting: bool,
+    pub drift_threshold: f32, // Drift angle (radians) that counts as drifting
+    pub rolling_resistance: f32,
+    pub grounded_wheels: usize, // Wheels touching the ground this frame
+    pub wheel_speed: f32, // Driven wheels' tread speed (m/s) - runs ahead of `speed` under wheelspin
+    pub fuel: f32, // Litres left in the tank - the engine cuts out when it runs dry
+    pub tire_wear: f32, // Average tread worn off the tyres, 0.0 fresh to 1.0 bald
+    pub boost: Boost,
+    pub boost_meter: f32, // Nitro left, 0.0 empty to 1.0 full
+    pub boosting: bool, // Nitro is firing this frame
+    pub forces: AppliedForces, // What the simulation pushed the body with last tick
 }
 
 impl Default for Car {
@@ -17,163 +56,945 @@ impl Default for Car {
         Self {
             speed: 0.0,
             max_speed: 60.0, // ~240 km/h (realistic M-series top speed)
-            acceleration: 800.0, // Responsive but not instant
-            turn_speed: 2.5, // Balanced steering response
-            motor_force: 35000.0, // Much stronger force for heavier car (1700kg)
+            steering: Steering::default(),
+            steer_angle: 0.0,
             brake_force: 25000.0, // Strong braking for heavy car
+            braking: false,
+            reversing: false,
+            reverse_hold_timer: 0.0,
+            reverse_delay: 0.3,
+            reverse_engage_speed: 0.5,
+            max_reverse_speed: 8.0, // ~30 km/h
+            engine: Engine::default(),
+            transmission: Transmission::Automatic,
+            gear: 1,
+            rpm: 0.0,
+            shift_timer: 0.0,
+            aero: Aero::default(),
+            suspension: Suspension::default(),
+            tire: TireSpec::default(),
+            front_grip: 1.0,
+            rear_grip: 0.95, // Slightly loose rear for throttle steering
+            handbrake_torque: 3000.0, // Enough to lock the rear wheels at any speed
+            weight_transfer: 1.0,
+            center_of_mass_height: 0.3,
+            drift_angle: 0.0,
+            front_slip: 0.0,
+            rear_slip: 0.0,
+            drifting: false,
+            drift_threshold: 0.17, // ~10 degrees
+            rolling_resistance: 0.015, // Typical road tyre rolling coefficient
+            grounded_wheels: 0,
+            wheel_speed: 0.0,
+            fuel: Engine::default().fuel_capacity, // Leaves the garage brimmed
+            tire_wear: 0.0,
+            boost: Boost::default(),
+            boost_meter: 0.5,
+            boosting: false,
+            forces: AppliedForces::default(),
         }
     }
 }
 
+impl Car {
+    /// Reference top speed, raised while the nitro fires
+    pub fn top_speed(&self) -> f32 {
+        if self.boosting { self.max_speed + self.boost.speed_bonus } else { self.max_speed }
+    }
+}
+
+/// Engine and gearbox tuning
+#[derive(Clone, Debug, Deserialize)]
+pub struct Engine {
+    pub idle_rpm: f32,
+    pub redline_rpm: f32, // Rev limiter cuts torque here
+    pub launch_rpm: f32, // Clutch slips to hold the engine here when pulling away
+    pub torque_curve: Vec<(f32, f32)>, // (rpm, Nm) points sorted by rpm, linearly interpolated
+    pub gear_ratios: Vec<f32>, // Forward gears, first to top
+    pub reverse_ratio: f32,
+    pub final_drive: f32,
+    pub drivetrain_efficiency: f32,
+    pub shift_up_rpm: f32, // Automatic upshift point
+    pub shift_down_rpm: f32, // Automatic downshift point
+    pub shift_time: f32, // Seconds of torque cut per gear change
+    pub fuel_capacity: f32, // Tank size in litres
+    pub fuel_consumption: f32, // Litres per second at full throttle on the redline
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self {
+            idle_rpm: 900.0,
+            redline_rpm: 7200.0,
+            launch_rpm: 3500.0,
+            // BMW M-series style - broad mid-range plateau that tails off near the limiter
+            torque_curve: vec![
+                (1000.0, 300.0),
+                (2500.0, 480.0),
+                (4000.0, 550.0),
+                (5500.0, 550.0),
+                (6500.0, 500.0),
+                (7200.0, 440.0),
+            ],
+            gear_ratios: vec![4.1, 2.6, 1.8, 1.4, 1.1, 0.9],
+            reverse_ratio: 3.7,
+            final_drive: 3.5,
+            drivetrain_efficiency: 0.9,
+            shift_up_rpm: 6800.0,
+            shift_down_rpm: 3000.0,
+            shift_time: 0.2,
+            fuel_capacity: 60.0,
+            fuel_consumption: 0.06, // Around a quarter of an hour flat out
+        }
+    }
+}
+
+impl Engine {
+    /// Litres burnt per second - harder throttle and higher revs drink more, idling still sips
+    pub fn fuel_burn(&self, throttle: f32, rpm: f32) -> f32 {
+        self.fuel_consumption * (rpm / self.redline_rpm).clamp(0.0, 1.0) * throttle.max(0.1)
+    }
+
+    /// Engine torque in Nm at the given rpm, zero past the limiter
+    pub fn torque_at(&self, rpm: f32) -> f32 {
+        if rpm >= self.redline_rpm {
+            return 0.0;
+        }
+        let Some(&(first_rpm, first_torque)) = self.torque_curve.first() else {
+            return 0.0;
+        };
+        if rpm <= first_rpm {
+            return first_torque;
+        }
+        for pair in self.torque_curve.windows(2) {
+            let ((rpm_a, torque_a), (rpm_b, torque_b)) = (pair[0], pair[1]);
+            if rpm <= rpm_b {
+                let t = (rpm - rpm_a) / (rpm_b - rpm_a).max(f32::EPSILON);
+                return torque_a + (torque_b - torque_a) * t;
+            }
+        }
+        self.torque_curve.last().map_or(0.0, |&(_, torque)| torque)
+    }
+
+    /// Gear ratio times final drive for a 1-based gear
+    pub fn overall_ratio(&self, gear: usize) -> f32 {
+        let index = gear.clamp(1, self.gear_ratios.len().max(1)) - 1;
+        self.gear_ratios.get(index).copied().unwrap_or(1.0) * self.final_drive
+    }
+
+    /// Reverse gear ratio times final drive
+    pub fn overall_reverse_ratio(&self) -> f32 {
+        self.reverse_ratio * self.final_drive
+    }
+
+    /// Engine speed implied by the wheels turning at `speed` m/s through `overall_ratio`
+    pub fn wheel_rpm(&self, speed: f32, wheel_radius: f32, overall_ratio: f32) -> f32 {
+        let wheel_angular_velocity = speed.abs() / wheel_radius;
+        wheel_angular_velocity * overall_ratio * 60.0 / (2.0 * PI)
+    }
+
+    /// Force the driven wheels put on the road for a given engine torque
+    pub fn wheel_force(&self, torque: f32, wheel_radius: f32, overall_ratio: f32) -> f32 {
+        torque * overall_ratio * self.drivetrain_efficiency / wheel_radius
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Transmission {
+    Automatic,
+    Manual, // Sequential shifting with E (up) and Q (down)
+}
+
+/// Raycast suspension settings shared by every wheel of a car
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct Suspension {
+    pub rest_length: f32, // Unloaded spring length above the wheel centre
+    pub travel: f32, // How far the wheel can move either side of rest
+    pub stiffness: f32, // Spring rate in N/m
+    pub damping: f32, // Damper rate in N per m/s
+    pub wheel_radius: f32, // Matches the GLB wheel mesh
+    pub tire_force_height: f32, // Lift tyre forces towards the centre of mass to limit body roll
+}
+
+impl Default for Suspension {
+    fn default() -> Self {
+        Self {
+            rest_length: 0.3,
+            travel: 0.15,
+            stiffness: 25000.0, // ~8cm static sag per corner
+            damping: 3000.0, // Slightly under-damped for a sporty feel
+            wheel_radius: 0.3,
+            tire_force_height: 0.25,
+        }
+    }
+}
+
+impl Car {
+    /// Road wheel angle full steering input asks for at the current speed
+    pub fn steering_lock(&self) -> f32 {
+        let speed_factor = (self.speed.abs() / self.max_speed).clamp(0.0, 1.0);
+        self.steering.max_angle + (self.steering.high_speed_angle - self.steering.max_angle) * speed_factor
+    }
+
+    /// Overall ratio of whichever gear is driving the wheels
+    pub fn drive_ratio(&self) -> f32 {
+        if self.reversing {
+            self.engine.overall_reverse_ratio()
+        } else {
+            self.engine.overall_ratio(self.gear)
+        }
+    }
+}
+
+/// Steering rack tuning - the front wheels turn at a limited rate and the tyres do the turning
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct Steering {
+    pub max_angle: f32, // Road wheel lock (radians) at a standstill
+    pub high_speed_angle: f32, // Lock at max_speed - less lock keeps fast corners from snapping the car round
+    pub steer_rate: f32, // How fast (rad/s) the wheels turn towards the requested angle
+    pub return_rate: f32, // How fast (rad/s) they self-centre once the wheel is let go
+    pub ackermann: f32, // 0.0 parallel steer, 1.0 full Ackermann - the inside wheel turns tighter
+}
+
+impl Default for Steering {
+    fn default() -> Self {
+        Self {
+            max_angle: 0.52, // ~30 degrees
+            high_speed_angle: 0.1, // Just under the tyres' peak slip angle
+            steer_rate: 1.5,
+            return_rate: 3.0,
+            ackermann: 0.8,
+        }
+    }
+}
+
+/// Road wheel angle for one front wheel given the centre-line angle (positive right) and the side
+/// the wheel sits on (+1.0 right, -1.0 left)
+pub fn ackermann_angle(steer_angle: f32, wheelbase: f32, track_width: f32, side: f32, ackermann: f32) -> f32 {
+    if steer_angle.abs() < 1e-4 {
+        return steer_angle;
+    }
+    // Both wheels aim at the same turn centre, level with the rear axle
+    let radius = wheelbase / steer_angle.tan(); // Signed like the angle
+    let ideal = (wheelbase / (radius - side * track_width / 2.0)).atan();
+    steer_angle + (ideal - steer_angle) * ackermann
+}
+
+/// Where a car's axles sit, measured from its discovered wheels in car space
+#[derive(Clone, Copy, Debug)]
+pub struct AxleLayout {
+    pub front_z: f32,
+    pub rear_z: f32,
+    pub wheelbase: f32,
+    pub track_width: f32,
+}
+
+impl AxleLayout {
+    pub fn of<'a>(wheels: impl Iterator<Item = (&'a WheelSuspension, bool)>) -> Self {
+        let (mut front_z, mut rear_z, mut track_width) = (0.0_f32, 0.0_f32, 0.1_f32);
+        for (wheel, front) in wheels {
+            if front {
+                front_z = front_z.min(wheel.anchor.z);
+            } else {
+                rear_z = rear_z.max(wheel.anchor.z);
+            }
+            track_width = track_width.max(wheel.anchor.x.abs() * 2.0);
+        }
+        Self { front_z, rear_z, wheelbase: (rear_z - front_z).max(0.1), track_width }
+    }
+}
+
+/// Aerodynamic tuning - drag and lift both grow with the square of airspeed, so top speed
+/// settles wherever the drivetrain's pull meets the drag
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct Aero {
+    pub air_density: f32, // kg/m^3
+    pub drag_coefficient: f32, // Cd
+    pub frontal_area: f32, // m^2
+    pub front_lift: f32, // Lift coefficient over the front axle - negative presses the car down
+    pub rear_lift: f32, // Lift coefficient over the rear axle - more downforce here than the front adds understeer
+}
+
+impl Default for Aero {
+    fn default() -> Self {
+        Self {
+            air_density: 1.225, // Sea level
+            drag_coefficient: 0.85, // Arcade drag - about double a real sedan so top gear tops out near max_speed
+            frontal_area: 2.4,
+            front_lift: -0.08, // ~1000 N of total downforce at max_speed
+            rear_lift: -0.11,
+        }
+    }
+}
+
+impl Aero {
+    /// Dynamic pressure (Pa) of the oncoming air
+    pub fn dynamic_pressure(&self, airspeed: f32) -> f32 {
+        0.5 * self.air_density * airspeed * airspeed
+    }
+
+    /// Drag force (N) opposing travel at this airspeed
+    pub fn drag(&self, airspeed: f32) -> f32 {
+        self.dynamic_pressure(airspeed) * self.drag_coefficient * self.frontal_area
+    }
+
+    /// Lift force (N) on the (front, rear) axle - negative values are downforce
+    pub fn lift(&self, airspeed: f32) -> (f32, f32) {
+        let pressure = self.dynamic_pressure(airspeed) * self.frontal_area;
+        (pressure * self.front_lift, pressure * self.rear_lift)
+    }
+}
+
+/// Nitro tuning - the meter itself lives on `Car`
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct Boost {
+    pub force: f32, // Extra drive force (N) on top of the torque curve
+    pub speed_bonus: f32, // How far past max_speed (m/s) the nitro can push the car
+    pub drain_rate: f32, // Meter used per second of boost
+    pub min_meter: f32, // Meter needed to light the nitro, so it doesn't stutter when nearly empty
+    pub recharge_rate: f32, // Meter regained per second while not boosting
+    pub drift_recharge: f32, // Extra meter per second while drifting
+    pub airtime_recharge: f32, // Extra meter per second with every wheel off the ground
+    pub near_miss_recharge: f32, // Meter for each obstacle passed closely at speed
+}
+
+impl Default for Boost {
+    fn default() -> Self {
+        Self {
+            force: 4000.0, // About half the engine's pull in a middle gear
+            speed_bonus: 12.0,
+            drain_rate: 0.25, // A full meter lasts four seconds
+            min_meter: 0.1,
+            recharge_rate: 0.02,
+            drift_recharge: 0.1,
+            airtime_recharge: 0.2,
+            near_miss_recharge: 0.1,
+        }
+    }
+}
+
+/// Every force and torque the car simulation applied in the last tick, kept for telemetry.
+/// Forces are in N along the car's axes, torques in Nm about its up axis
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct AppliedForces {
+    pub suspension: f32, // Springs and dampers holding the body up
+    pub drive: f32, // Engine force asked of the rear tyres, after stability control
+    pub brake: f32, // Pedal brake force asked of the tyres
+    pub tire_longitudinal: f32, // What the tyres delivered along the car, rolling resistance included
+    pub tire_lateral: f32, // Cornering force, positive pushing right
+    pub rolling_resistance: f32,
+    pub boost: f32,
+    pub drag: f32, // Along the airflow rather than the car
+    pub front_lift: f32, // Negative presses the axle down
+    pub rear_lift: f32,
+    pub stability_brake: f32, // Stability control brake torque on the outside front wheel
+    pub angular_damping: f32,
+    pub yaw_damping: f32,
+}
+
 #[derive(Component)]
 pub struct CameraTarget;
 
+#[derive(Component)]
+pub struct BrakeLight; // Glows while the car is braking
+
+#[derive(Component)]
+pub struct ReverseLight; // Glows while reverse is engaged
+
 #[derive(Component)]
 pub struct Wheel;
 
 #[derive(Component)]
 pub struct FrontWheel; // Component to mark front wheels for steering
 
+/// Spinning half of a wheel. The GLB wheel node is the hub that rides the suspension and steers,
+/// and its meshes are moved under this so rolling never fights the steering
+#[derive(Component)]
+pub struct WheelRim {
+    pub axle: Vec3, // The car's X axis in the hub's local space
+    pub angle: f32, // Accumulated roll (radians)
+}
+
+/// Per-wheel suspension state, attached to each discovered wheel node
+#[derive(Component)]
+pub struct WheelSuspension {
+    pub car: Entity,
+    pub anchor: Vec3, // Wheel centre in car space as modelled in the GLB
+    pub base_translation: Vec3, // Original local translation of the wheel node
+    pub base_rotation: Quat, // Original local rotation - steering turns the hub from here
+    pub parent_to_car: Transform, // Wheel node's parent expressed in car space
+    pub length: f32, // Current spring length
+    pub compression: f32, // Positive when compressed past rest
+    pub load: f32, // Suspension force pushing the car up (N)
+    pub grounded: bool,
+    pub contact_point: Vec3,
+    pub contact_normal: Vec3,
+    pub surface: SurfaceType, // Ground under the wheel, or the last ground it touched while airborne
+    pub angular_velocity: f32, // Wheel spin (rad/s), positive rolling forwards
+    pub slip_ratio: f32,
+    pub slip_angle: f32,
+    pub steer_angle: f32, // Road wheel angle (radians), positive steering right - front wheels only
+    pub wear: f32, // Tread worn off this tyre, 0.0 fresh to 1.0 bald
+}
+
+impl WheelSuspension {
+    pub fn new(car: Entity, anchor: Vec3, base: Transform, parent_to_car: Transform) -> Self {
+        Self {
+            car,
+            anchor,
+            base_translation: base.translation,
+            base_rotation: base.rotation,
+            parent_to_car,
+            length: 0.0,
+            compression: 0.0,
+            load: 0.0,
+            grounded: false,
+            contact_point: Vec3::ZERO,
+            contact_normal: Vec3::Y,
+            surface: SurfaceType::Asphalt,
+            angular_velocity: 0.0,
+            slip_ratio: 0.0,
+            slip_angle: 0.0,
+            steer_angle: 0.0,
+            wear: 0.0,
+        }
+    }
+}
+
 pub struct CarPlugin;
 
 impl Plugin for CarPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (car_physics_system, wheel_rotation_system, front_wheel_steering_system).run_if(in_state(GameState::InGame)));
+        app.add_systems(FixedUpdate, (
+            service_system,
+            gearbox_system,
+            steering_system,
+            suspension_system,
+            car_physics_system,
+        ).chain().in_set(CarSimulationSet).run_if(in_state(GameState::InGame)))
+            .add_systems(Update, (
+                wheel_suspension_visual_system,
+                wheel_rotation_system,
+                front_wheel_steering_system,
+                car_lights_system,
+            ).run_if(in_state(GameState::InGame)));
+    }
+}
+
+// Pit service - brim the tank and fit fresh tyres
+fn service_system(
+    mut car_query: Query<(Entity, &mut Car, &CarInput)>,
+    mut wheel_query: Query<&mut WheelSuspension>,
+) {
+    for (entity, mut car, input) in car_query.iter_mut() {
+        if !input.service {
+            continue;
+        }
+
+        car.fuel = car.engine.fuel_capacity;
+        car.tire_wear = 0.0;
+        for mut wheel in wheel_query.iter_mut().filter(|wheel| wheel.car == entity) {
+            wheel.wear = 0.0;
+        }
     }
 }
 
+fn gearbox_system(
+    time: Res<Time>,
+    mut car_query: Query<(&mut Car, &CarInput)>,
+) {
+    let dt = time.delta_secs();
+
+    for (mut car, input) in car_query.iter_mut() {
+        // Toggle between automatic and sequential manual shifting
+        if input.toggle_transmission {
+            car.transmission = match car.transmission {
+                Transmission::Automatic => Transmission::Manual,
+                Transmission::Manual => Transmission::Automatic,
+            };
+        }
+
+        // Brake pedal stops the car, then engages reverse if it is still held; throttle does the opposite
+        let stopped = car.speed.abs() < car.reverse_engage_speed;
+        if car.reversing {
+            car.braking = input.throttle > 0.0 && !stopped;
+            if input.throttle > 0.0 && stopped {
+                car.reversing = false;
+                car.gear = 1;
+            }
+        } else {
+            car.braking = input.brake > 0.0 && !stopped;
+            if input.brake > 0.0 && stopped && input.throttle <= 0.0 {
+                car.reverse_hold_timer += dt;
+                if car.reverse_hold_timer >= car.reverse_delay {
+                    car.reversing = true;
+                    car.gear = 1;
+                }
+            }
+        }
+        if !car.braking && (car.reversing || input.brake <= 0.0) {
+            car.reverse_hold_timer = 0.0;
+        }
+
+        car.shift_timer = (car.shift_timer - dt).max(0.0);
+        let top_gear = car.engine.gear_ratios.len().max(1);
+        let wheel_rpm = car.engine.wheel_rpm(car.wheel_speed, car.suspension.wheel_radius, car.engine.overall_ratio(car.gear));
+
+        // Only one gear change at a time - wait for the current shift to finish, reverse has a single gear
+        if car.shift_timer <= 0.0 && !car.reversing {
+            let target_gear = match car.transmission {
+                Transmission::Automatic => {
+                    if wheel_rpm > car.engine.shift_up_rpm && car.gear < top_gear {
+                        car.gear + 1
+                    } else if wheel_rpm < car.engine.shift_down_rpm && car.gear > 1 {
+                        car.gear - 1
+                    } else {
+                        car.gear
+                    }
+                }
+                Transmission::Manual => {
+                    if input.shift_up && car.gear < top_gear {
+                        car.gear + 1
+                    } else if input.shift_down && car.gear > 1 {
+                        car.gear - 1
+                    } else {
+                        car.gear
+                    }
+                }
+            };
+
+            if target_gear != car.gear {
+                car.gear = target_gear;
+                car.shift_timer = car.engine.shift_time;
+            }
+        }
+    }
+}
+
+fn steering_system(
+    time: Res<Time>,
+    mut car_query: Query<(Entity, &mut Car, &CarInput, Option<&CarDamage>)>,
+    mut wheel_query: Query<(&mut WheelSuspension, Has<FrontWheel>)>,
+) {
+    let dt = time.delta_secs();
+
+    for (car_entity, mut car, input, damage) in car_query.iter_mut() {
+        // Less lock at speed - bent suspension pulls to one side
+        let steering = car.steering;
+        let lock = car.steering_lock();
+        let steer = (input.steer + damage.map_or(0.0, |damage| damage.alignment)).clamp(-1.0, 1.0);
+
+        // Wind the wheels towards the request, centring faster once the wheel is let go
+        let rate = if input.steer == 0.0 { steering.return_rate } else { steering.steer_rate } * dt;
+        car.steer_angle += (steer * lock - car.steer_angle).clamp(-rate, rate);
+
+        let layout = AxleLayout::of(wheel_query.iter().filter(|(wheel, _)| wheel.car == car_entity));
+        for (mut wheel, front) in wheel_query.iter_mut().filter(|(wheel, _)| wheel.car == car_entity) {
+            wheel.steer_angle = if front {
+                ackermann_angle(car.steer_angle, layout.wheelbase, layout.track_width, wheel.anchor.x.signum(), steering.ackermann)
+            } else {
+                0.0
+            };
+        }
+    }
+}
+
+fn suspension_system(
+    rapier_context: ReadRapierContext,
+    mut car_query: Query<(&Transform, &Velocity, &ReadMassProperties, &mut ExternalForce, &mut Car)>,
+    mut wheel_query: Query<&mut WheelSuspension>,
+    surface_query: Query<&SurfaceType>,
+) {
+    // Reset forces each frame - suspension is the first stage of the force pipeline
+    for (_, _, _, mut force, mut car) in car_query.iter_mut() {
+        force.force = Vec3::ZERO;
+        force.torque = Vec3::ZERO;
+        car.grounded_wheels = 0;
+        car.forces = AppliedForces::default();
+    }
+
+    let Ok(rapier_context) = rapier_context.single() else {
+        return;
+    };
+
+    for mut wheel in wheel_query.iter_mut() {
+        let Ok((transform, velocity, mass_props, mut force, mut car)) = car_query.get_mut(wheel.car) else {
+            continue;
+        };
+        let suspension = car.suspension;
+        let up = *transform.up();
+        let center_of_mass = transform.transform_point(mass_props.local_center_of_mass);
+
+        // Cast from the top of the strut down through the bottom of the tyre
+        let mount = transform.transform_point(wheel.anchor + Vec3::Y * suspension.rest_length);
+        let max_length = suspension.rest_length + suspension.travel;
+        let filter = QueryFilter::default()
+            .exclude_rigid_body(wheel.car)
+            .exclude_sensors();
+
+        let Some((hit_entity, hit)) = rapier_context.cast_ray_and_get_normal(
+            mount,
+            -up,
+            max_length + suspension.wheel_radius,
+            true,
+            filter,
+        ) else {
+            // Wheel hangs at full droop
+            wheel.length = max_length;
+            wheel.compression = suspension.rest_length - max_length;
+            wheel.load = 0.0;
+            wheel.grounded = false;
+            continue;
+        };
+
+        let min_length = suspension.rest_length - suspension.travel;
+        wheel.length = (hit.time_of_impact - suspension.wheel_radius).clamp(min_length, max_length);
+        wheel.compression = suspension.rest_length - wheel.length;
+
+        // Spring pushes out, damper resists the car closing in on the surface under the wheel
+        let point_velocity = velocity.linvel + velocity.angvel.cross(hit.point - center_of_mass);
+        let spring_force = suspension.stiffness * wheel.compression;
+        let damper_force = -suspension.damping * point_velocity.dot(hit.normal);
+        wheel.load = (spring_force + damper_force).max(0.0); // Springs can push but never pull
+
+        *force += ExternalForce::at_point(up * wheel.load, hit.point, center_of_mass);
+
+        wheel.grounded = true;
+        wheel.contact_point = hit.point;
+        wheel.contact_normal = hit.normal;
+        wheel.surface = surface_query.get(hit_entity).copied().unwrap_or_default();
+        car.grounded_wheels += 1;
+        car.forces.suspension += wheel.load;
+    }
+}
+
+#[allow(clippy::type_complexity)]
 fn car_physics_system(
-    _time: Res<Time>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut car_query: Query<(&mut ExternalForce, &ExternalImpulse, &Transform, &mut Car, &Velocity)>,
+    time: Res<Time>,
+    mut car_query: Query<(Entity, &mut ExternalForce, &ExternalImpulse, &Transform, &mut Car, &Velocity, &ReadMassProperties, &CarInput, &mut DriverAssists, Option<&CarDamage>)>,
+    mut wheel_query: Query<(&mut WheelSuspension, Has<FrontWheel>)>,
 ) {
-    for (mut force, _impulse, transform, mut car, velocity) in car_query.iter_mut() {
+    let dt = time.delta_secs().max(f32::EPSILON);
+
+    for (car_entity, mut force, _impulse, transform, mut car, velocity, mass_props, input, mut assists, damage) in car_query.iter_mut() {
+        let damage = damage.copied().unwrap_or_default();
         // Calculate current speed from velocity
         let current_velocity = velocity.linvel;
         let forward = *transform.forward();
-        let right = *transform.right();
+        let up = *transform.up();
         car.speed = current_velocity.dot(forward);
+        let center_of_mass = transform.transform_point(mass_props.local_center_of_mass);
 
-        // Reset forces each frame
-        force.force = Vec3::ZERO;
-        force.torque = Vec3::ZERO;
+        // In reverse the pedals swap roles - brake drives backwards and throttle brakes
+        let (drive_pedal, brake_pedal) = if car.reversing {
+            (input.brake, input.throttle)
+        } else {
+            (input.throttle, input.brake)
+        };
+
+        // Engine speed follows the wheels, with the clutch slipping to hold launch revs from a standstill
+        let drive_ratio = car.drive_ratio();
+        let wheel_rpm = car.engine.wheel_rpm(car.wheel_speed, car.suspension.wheel_radius, drive_ratio);
+        let clutch_rpm = car.engine.idle_rpm + (car.engine.launch_rpm - car.engine.idle_rpm) * drive_pedal;
+        car.rpm = wheel_rpm.max(clutch_rpm).min(car.engine.redline_rpm);
 
-        // Calculate current speed percentage for acceleration curve
-        let speed_percentage = (current_velocity.length() / car.max_speed).clamp(0.0, 1.0);
-        
-        // BMW M-series style acceleration curve - strong initial punch, then levels off
-        let acceleration_curve = if speed_percentage < 0.3 {
-            1.0 // Full power in low speeds (0-30% of max speed)
-        } else if speed_percentage < 0.6 {
-            0.8 // Good power in mid speeds (30-60%)
-        } else if speed_percentage < 0.85 {
-            0.5 // Reduced power at high speeds (60-85%)
+        // The engine burns fuel by throttle and revs, and cuts out once the tank is dry
+        let fuel_burn = car.engine.fuel_burn(drive_pedal, car.rpm) * dt;
+        car.fuel = (car.fuel - fuel_burn).max(0.0);
+        let engine_running = car.fuel > 0.0;
+        if !engine_running {
+            car.rpm = 0.0;
+        }
+
+        // Handle forward/backward movement through the torque curve and current gear - the handbrake disengages the clutch
+        let mut drive_force: f32 = 0.0;
+        let reverse_limited = car.reversing && -car.speed >= car.max_reverse_speed;
+        if drive_pedal > 0.0 && engine_running && car.shift_timer <= 0.0 && !reverse_limited && !input.handbrake {
+            let torque = car.engine.torque_at(wheel_rpm.max(clutch_rpm)) * drive_pedal * damage.power_scale();
+            let wheel_force = car.engine.wheel_force(torque, car.suspension.wheel_radius, drive_ratio);
+            drive_force += if car.reversing { -wheel_force } else { wheel_force };
+        }
+
+        // Brakes oppose the direction of travel, fading out near a standstill so they don't push back
+        let brake_direction = (car.speed / car.reverse_engage_speed).clamp(-1.0, 1.0);
+        let mut brake_force = 0.0;
+        if car.braking {
+            brake_force = car.brake_force * brake_pedal * brake_direction;
+        }
+
+        // Axle geometry from this car's wheels, used to share weight transfer between them
+        let car_wheels = || wheel_query.iter().filter(|(wheel, _)| wheel.car == car_entity);
+        let layout = AxleLayout::of(car_wheels());
+        let rear_grounded = car_wheels().filter(|(wheel, front)| wheel.grounded && !*front).count().max(1) as f32;
+
+        // Weight shifts back under power, forward under braking and to the outside wheels in a turn
+        let mass = mass_props.mass.max(1.0);
+        let yaw_rate = velocity.angvel.dot(up);
+        let longitudinal_accel = (drive_force - brake_force) / mass;
+        let lateral_accel = car.speed * yaw_rate; // Centripetal - positive when turning left
+        let longitudinal_transfer = mass * longitudinal_accel * car.center_of_mass_height / layout.wheelbase * car.weight_transfer;
+        let lateral_transfer = mass * lateral_accel * car.center_of_mass_height / layout.track_width * car.weight_transfer;
+        let static_load = mass * 9.81 / 4.0;
+
+        // Nitro pushes the body beyond what the torque curve and rear tyres give, up to a raised top speed.
+        // It only drives while the wheels are down and drains the meter as it goes
+        let boost_needed = if car.boosting { f32::EPSILON } else { car.boost.min_meter };
+        car.boosting = input.boost && car.boost_meter >= boost_needed && engine_running && !car.reversing;
+        if car.boosting {
+            let headroom = ((car.top_speed() - car.speed) / 5.0).clamp(0.0, 1.0); // Fade out over the last few m/s
+            let boost_force = car.boost.force * headroom * (car.grounded_wheels as f32 / 4.0);
+            force.force += forward * boost_force;
+            car.forces.boost = boost_force;
+            car.boost_meter = (car.boost_meter - car.boost.drain_rate * dt).max(0.0);
+        }
+
+        // Stability control brakes the outside front wheel and eases off the power while the tail slides
+        let stability_torque = assists.stability_brake_torque(car.drift_angle, yaw_rate);
+        assists.stability_control_active = stability_torque > 0.0;
+        if assists.stability_control_active {
+            drive_force *= 0.5;
+        }
+        assists.abs_active = false;
+        assists.traction_control_active = false;
+        car.forces.drive = drive_force;
+        car.forces.brake = brake_force;
+        car.forces.stability_brake = stability_torque;
+
+        // Each wheel spins under engine and brake torque, and the tyre model turns its slip into force
+        let grounded_wheels = car.grounded_wheels.max(1) as f32;
+        let wheel_radius = car.suspension.wheel_radius;
+        let tire = car.tire;
+        let (mut front_slip, mut rear_slip) = (0.0_f32, 0.0_f32);
+        let mut rear_saturation = 0.0;
+        let mut driven_wheel_speed = 0.0;
+        let (mut total_wear, mut wheel_count) = (0.0, 0.0);
+        let (mut total_tire_force, mut total_rolling_resistance) = (Vec3::ZERO, 0.0);
+        for (mut wheel, front) in wheel_query.iter_mut().filter(|(wheel, _)| wheel.car == car_entity) {
+            total_wear += wheel.wear;
+            wheel_count += 1.0;
+
+            // Rear-wheel drive through an open differential, brakes on every wheel, handbrake on the rear.
+            // Traction control and ABS work from the slip this wheel had last frame
+            let mut drive_torque = if front { 0.0 } else { drive_force / rear_grounded * wheel_radius };
+            let traction_scale = assists.traction_scale(wheel.slip_ratio);
+            if drive_torque != 0.0 && traction_scale < 1.0 {
+                drive_torque *= traction_scale;
+                assists.traction_control_active = true;
+            }
+
+            let mut brake_torque = brake_force.abs() / grounded_wheels * wheel_radius;
+            let abs_scale = assists.abs_brake_scale(wheel.slip_ratio);
+            if brake_torque > 0.0 && abs_scale < 1.0 {
+                brake_torque *= abs_scale;
+                assists.abs_active = true;
+            }
+            if front && wheel.anchor.x.signum() == yaw_rate.signum() {
+                brake_torque += stability_torque; // Outside of the turn
+            }
+            if input.handbrake && !front {
+                brake_torque += car.handbrake_torque;
+            }
+
+            if !wheel.grounded {
+                // Airborne wheels spin freely
+                wheel.angular_velocity += drive_torque / tire.wheel_inertia * dt;
+                wheel.angular_velocity = apply_brake_torque(wheel.angular_velocity, brake_torque / tire.wheel_inertia * dt);
+                wheel.slip_ratio = 0.0;
+                wheel.slip_angle = 0.0;
+                if !front {
+                    driven_wheel_speed += wheel.angular_velocity * wheel_radius / 2.0;
+                }
+                continue;
+            }
+
+            // Front wheels point where the steering aims them, which is what turns the car
+            let normal = wheel.contact_normal;
+            let heading = Quat::from_axis_angle(normal, -wheel.steer_angle) * forward;
+            let wheel_forward = (heading - normal * heading.dot(normal)).normalize_or_zero();
+            let wheel_right = wheel_forward.cross(normal);
+            let application_point = wheel.contact_point + up * car.suspension.tire_force_height;
+            let point_velocity = current_velocity + velocity.angvel.cross(wheel.contact_point - center_of_mass);
+            let rolling_velocity = point_velocity.dot(wheel_forward);
+            let lateral_velocity = point_velocity.dot(wheel_right);
+
+            // Grip depends on how much of the car's weight this wheel carries right now
+            let grip_load = (static_load
+                + wheel.anchor.z.signum() * longitudinal_transfer * 0.5
+                + wheel.anchor.x.signum() * lateral_transfer * 0.5)
+                .max(0.0);
+            let axle_grip = if front { car.front_grip } else { car.rear_grip };
+            let surface = wheel.surface.properties();
+            let surface_friction = surface.grip * axle_grip * tire.wear_grip(wheel.wear);
+            let slip_angle = tire.slip_angle(rolling_velocity, lateral_velocity);
+
+            // Integrate wheel spin implicitly against the tyre's stiffness so it settles without oscillating
+            let tread_speed = wheel.angular_velocity * wheel_radius;
+            let road_torque = tire.force(tire.slip_ratio(tread_speed, rolling_velocity), slip_angle, grip_load, surface_friction).x * wheel_radius;
+            let stiffness = tire.longitudinal_stiffness(grip_load, surface_friction) / rolling_velocity.abs().max(tire.low_speed);
+            let effective_inertia = tire.wheel_inertia + dt * stiffness * wheel_radius * wheel_radius;
+            wheel.angular_velocity += (drive_torque - road_torque) / effective_inertia * dt;
+            wheel.angular_velocity = apply_brake_torque(wheel.angular_velocity, brake_torque / effective_inertia * dt);
+
+            wheel.slip_ratio = tire.slip_ratio(wheel.angular_velocity * wheel_radius, rolling_velocity);
+            wheel.slip_angle = slip_angle;
+            let tire_force = tire.force(wheel.slip_ratio, slip_angle, grip_load, surface_friction);
+
+            // Never push harder than it takes to stop the contact patch sliding this step
+            let mass_share = mass / grounded_wheels;
+            let longitudinal_limit = mass_share * (wheel.angular_velocity * wheel_radius - rolling_velocity).abs() / dt;
+            let lateral_limit = mass_share * lateral_velocity.abs() / dt;
+            let longitudinal = tire_force.x.clamp(-longitudinal_limit, longitudinal_limit);
+            let lateral = tire_force.y.clamp(-lateral_limit, lateral_limit);
+            let rolling_resistance = -rolling_velocity.signum() * car.rolling_resistance * surface.rolling_resistance * wheel.load;
+
+            // Tread wears away with distance rolled, and much faster while the contact patch slides
+            let sliding_speed = Vec2::new(wheel.angular_velocity * wheel_radius - rolling_velocity, lateral_velocity).length();
+            wheel.wear = (wheel.wear + tire.wear_rate(rolling_velocity.abs(), sliding_speed) * dt).min(1.0);
+
+            if front {
+                front_slip = front_slip.max(lateral_velocity.abs());
+            } else {
+                rear_slip = rear_slip.max(lateral_velocity.abs());
+                rear_saturation += (slip_angle.abs() / tire.peak_slip_angle).min(1.0) / 2.0;
+                driven_wheel_speed += wheel.angular_velocity * wheel_radius / 2.0;
+            }
+
+            let tire_force = wheel_forward * (longitudinal + rolling_resistance) + wheel_right * lateral;
+            *force += ExternalForce::at_point(tire_force, application_point, center_of_mass);
+            total_tire_force += tire_force;
+            total_rolling_resistance += rolling_resistance;
+        }
+        car.wheel_speed = driven_wheel_speed;
+        car.forces.tire_longitudinal = total_tire_force.dot(forward);
+        car.forces.tire_lateral = total_tire_force.dot(*transform.right());
+        car.forces.rolling_resistance = total_rolling_resistance;
+        car.tire_wear = if wheel_count > 0.0 { total_wear / wheel_count } else { 0.0 };
+
+        // Drift state for scoring and effects - angle between where the car points and where it travels
+        let planar_velocity = current_velocity - up * current_velocity.dot(up);
+        car.front_slip = front_slip;
+        car.rear_slip = rear_slip;
+        car.drift_angle = if planar_velocity.length() > 2.0 && car.speed > 0.0 {
+            forward.cross(planar_velocity.normalize()).dot(up).asin() // Positive when sliding to the left
         } else {
-            0.2 // Minimal power near top speed (85-100%)
+            0.0
         };
+        car.drifting = car.drift_angle.abs() > car.drift_threshold;
 
-        // Handle forward/backward movement with realistic acceleration curve
-        if keyboard_input.pressed(KeyCode::ArrowUp) || keyboard_input.pressed(KeyCode::KeyW) {
-            let motor_force = forward * car.motor_force * acceleration_curve;
-            force.force += motor_force;
-        } 
-        
-        if keyboard_input.pressed(KeyCode::ArrowDown) || keyboard_input.pressed(KeyCode::KeyS) {
-            let brake_force = -forward * car.brake_force;
-            force.force += brake_force;
-        }
-        
-        // Lateral grip - prevent sliding sideways (key for reducing skidding feeling)
-        let lateral_velocity = current_velocity.dot(right);
-        if lateral_velocity.abs() > 0.1 {
-            let lateral_grip = -right * lateral_velocity * 15.0; // Strong lateral grip
-            force.force += lateral_grip;
-        }
-        
-        // Simple drag when coasting
-        if !keyboard_input.pressed(KeyCode::ArrowUp) && !keyboard_input.pressed(KeyCode::KeyW) && 
-           !keyboard_input.pressed(KeyCode::ArrowDown) && !keyboard_input.pressed(KeyCode::KeyS) {
-            let drag = -current_velocity * 2.0; // Reduced drag for better momentum
-            force.force += drag;
-        }
-
-        // Improved turning - more responsive and proper for arcade racing
-        let turn_effectiveness = (1.0 - speed_percentage * 0.2).max(0.6); // Less reduction at high speed
-        let base_turn_force = car.turn_speed * 4000.0; // Stronger turning force
-        
-        if keyboard_input.pressed(KeyCode::ArrowLeft) || keyboard_input.pressed(KeyCode::KeyA) {
-            let turn_torque = Vec3::Y * base_turn_force * turn_effectiveness;
-            force.torque += turn_torque;
-        }
-        if keyboard_input.pressed(KeyCode::ArrowRight) || keyboard_input.pressed(KeyCode::KeyD) {
-            let turn_torque = Vec3::Y * -base_turn_force * turn_effectiveness;
-            force.torque += turn_torque;
+        // The nitro refills slowly on its own, faster while drifting or flying
+        if !car.boosting {
+            let mut recharge = car.boost.recharge_rate;
+            if car.drifting {
+                recharge += car.boost.drift_recharge;
+            }
+            if car.grounded_wheels == 0 {
+                recharge += car.boost.airtime_recharge;
+            }
+            car.boost_meter = (car.boost_meter + recharge * dt).min(1.0);
         }
 
         // Balanced stability - prevent spinning but allow responsive turning
         let angular_damping = -velocity.angvel * 5.0; // Reduced for better turning response
         force.torque += angular_damping;
+        car.forces.angular_damping = angular_damping.dot(up);
 
-        // Add slight downforce at speed to keep car planted
-        if speed_percentage > 0.2 {
-            let downforce = Vec3::Y * -speed_percentage * 1000.0;
-            force.force += downforce;
-        }
+        // Yaw stability - the turn itself comes from the front tyres, this only damps the rotation so the
+        // short wheelbase doesn't wag under power. It fades as the rear tyres let go so the tail can still step out
+        let yaw_damping = -up * yaw_rate * 800.0 * (1.0 - rear_saturation * 0.5);
+        let yaw_damping = yaw_damping * car.grounded_wheels as f32 / 4.0;
+        force.torque += yaw_damping;
+        car.forces.yaw_damping = yaw_damping.dot(up);
 
-        // Simple speed limiting
-        if current_velocity.length() > car.max_speed {
-            let excess_velocity = current_velocity - current_velocity.normalize() * car.max_speed;
-            force.force -= excess_velocity * 800.0;
+        // Aero acts every tick whatever the pedals do - drag along the airflow, lift at each axle
+        // at the height of the centre of mass so it loads the springs without pitching the car
+        let airspeed = current_velocity.length();
+        let air_drag = -current_velocity.normalize_or_zero() * car.aero.drag(airspeed) * damage.drag_scale();
+        force.force += air_drag;
+        let (front_lift, rear_lift) = car.aero.lift(airspeed);
+        car.forces.drag = air_drag.length();
+        car.forces.front_lift = front_lift;
+        car.forces.rear_lift = rear_lift;
+        for (lift, axle_z) in [(front_lift, layout.front_z), (rear_lift, layout.rear_z)] {
+            let axle = transform.transform_point(Vec3::new(0.0, mass_props.local_center_of_mass.y, axle_z));
+            *force += ExternalForce::at_point(up * lift, axle, center_of_mass);
         }
     }
 }
 
+// Brake torque slows a spinning wheel but can only bring it to rest, never spin it backwards
+fn apply_brake_torque(angular_velocity: f32, max_change: f32) -> f32 {
+    if angular_velocity.abs() <= max_change {
+        0.0
+    } else {
+        angular_velocity - angular_velocity.signum() * max_change
+    }
+}
+
+fn wheel_suspension_visual_system(
+    car_query: Query<&Car>,
+    mut wheel_query: Query<(&mut Transform, &WheelSuspension), Without<Car>>,
+) {
+    for (mut wheel_transform, wheel) in wheel_query.iter_mut() {
+        let Ok(car) = car_query.get(wheel.car) else {
+            continue;
+        };
+
+        // Move the wheel along the strut so it sits on the ray hit (or hangs at full droop)
+        let car_space_offset = Vec3::Y * (car.suspension.rest_length - wheel.length);
+        let parent_offset = wheel.parent_to_car.rotation.inverse() * car_space_offset / wheel.parent_to_car.scale;
+        wheel_transform.translation = wheel.base_translation + parent_offset;
+    }
+}
+
 fn wheel_rotation_system(
     time: Res<Time>,
-    car_query: Query<&Car>,
-    mut wheel_query: Query<&mut Transform, (With<Wheel>, Without<Car>)>,
+    wheel_query: Query<&WheelSuspension>,
+    mut rim_query: Query<(&mut Transform, &mut WheelRim, &ChildOf)>,
 ) {
-    if let Ok(car) = car_query.single() {
-        let dt = time.delta_secs();
-        
-        // Calculate wheel rotation based on car speed
-        // BMW M-series typically has 18-19" wheels
-        let wheel_radius = 0.35; // Realistic wheel radius for M-series
-        let wheel_circumference = 2.0 * PI * wheel_radius;
-        let rotation_speed = car.speed / wheel_circumference;
-        
-        for mut wheel_transform in wheel_query.iter_mut() {
-            // Rotate wheels around their local X axis (proper rolling motion for car wheels)
-            // Negative rotation because forward movement should rotate wheels forward
-            wheel_transform.rotate_local_x(-rotation_speed * dt);
-        }
+    let dt = time.delta_secs();
+
+    for (mut rim_transform, mut rim, child_of) in rim_query.iter_mut() {
+        let Ok(wheel) = wheel_query.get(child_of.parent()) else {
+            continue;
+        };
+
+        // Spin at the wheel's own rate so it locks under braking and spins up in a burnout.
+        // Rolling forwards turns the top of the wheel towards -Z, which is negative about the axle
+        rim.angle = (rim.angle - wheel.angular_velocity * dt) % (2.0 * PI);
+        rim_transform.rotation = Quat::from_axis_angle(rim.axle, rim.angle);
     }
 }
 
 fn front_wheel_steering_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut front_wheel_query: Query<&mut Transform, (With<FrontWheel>, Without<Car>)>,
+    mut front_wheel_query: Query<(&mut Transform, &WheelSuspension), With<FrontWheel>>,
 ) {
-    // Calculate steering angle based on input
-    let max_steering_angle = 30.0_f32.to_radians(); // 30 degrees max steering
-    let mut target_steering = 0.0;
-    
-    if keyboard_input.pressed(KeyCode::ArrowLeft) || keyboard_input.pressed(KeyCode::KeyA) {
-        target_steering = max_steering_angle; // Turn left
-    } else if keyboard_input.pressed(KeyCode::ArrowRight) || keyboard_input.pressed(KeyCode::KeyD) {
-        target_steering = -max_steering_angle; // Turn right
-    }
-    
-    for mut front_wheel_transform in front_wheel_query.iter_mut() {
-        // Reset rotation and apply both rolling and steering
-        // For front wheels, we need to apply steering rotation around Y-axis
-        // The rolling rotation (X-axis) is handled by the main wheel_rotation_system
-        
-        // Extract current rotation to preserve rolling motion
-        let current_rotation = front_wheel_transform.rotation;
-        
-        // Apply steering by setting the Y rotation directly while preserving X rotation (rolling)
-        let euler = current_rotation.to_euler(EulerRot::XYZ);
-        front_wheel_transform.rotation = Quat::from_euler(EulerRot::XYZ, euler.0, target_steering, euler.2);
-    }
-} 
\ No newline at end of file
+    for (mut front_wheel_transform, wheel) in front_wheel_query.iter_mut() {
+        // Show the same angle the tyre model steers with, turning the hub about the car's up axis
+        let steering = Quat::from_rotation_y(-wheel.steer_angle); // Positive angle turns left
+        let parent_rotation = wheel.parent_to_car.rotation;
+        front_wheel_transform.rotation = parent_rotation.inverse() * steering * parent_rotation * wheel.base_rotation;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn car_lights_system(
+    car_query: Query<&Car>,
+    parents: Query<&ChildOf>,
+    mut brake_light_query: Query<(Entity, &mut PointLight), (With<BrakeLight>, Without<ReverseLight>)>,
+    mut reverse_light_query: Query<(Entity, &mut PointLight), (With<ReverseLight>, Without<BrakeLight>)>,
+) {
+    // Lamps may hang off the car itself or off a node deep inside its model
+    let owning_car = |lamp| parents.iter_ancestors(lamp).find_map(|ancestor| car_query.get(ancestor).ok());
+
+    for (entity, mut light) in brake_light_query.iter_mut() {
+        if let Some(car) = owning_car(entity) {
+            light.intensity = if car.braking { 20_000.0 } else { 2_000.0 }; // Dim tail light when not braking
+        }
+    }
+
+    for (entity, mut light) in reverse_light_query.iter_mut() {
+        if let Some(car) = owning_car(entity) {
+            light.intensity = if car.reversing { 20_000.0 } else { 0.0 };
+        }
+    }
+}
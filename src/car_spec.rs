@@ -0,0 +1,460 @@
+use crate::*;
+use crate::menu::GameState;
+use crate::car::{Car, Aero, Boost, Engine, Steering, Suspension, BrakeLight, ReverseLight, Wheel, FrontWheel, WheelRim, WheelSuspension};
+use crate::tire::TireSpec;
+use bevy::asset::{AssetLoader, LoadContext, io::Reader};
+use bevy::gltf::{GltfAssetLabel, GltfExtras};
+use bevy::scene::{InstanceId, SceneInstance, SceneSpawner};
+use bevy_rapier3d::prelude::MassProperties;
+use serde::Deserialize;
+use thiserror::Error;
+
+pub struct CarSpecPlugin;
+
+impl Plugin for CarSpecPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<CarSpec>()
+            .init_asset_loader::<CarSpecLoader>()
+            .add_systems(Startup, load_car_roster)
+            .add_systems(Update, (apply_car_specs, discover_car_parts).chain().run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Everything that makes one car model drive and look the way it does, loaded from a `.car.ron` file.
+/// Edits to the file are applied to live cars without restarting the game.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct CarSpec {
+    pub name: String,
+    pub model: String, // GLB path relative to the assets folder
+    pub model_offset: [f32; 3], // Shifts the model so it lines up with the collider
+    #[serde(default)]
+    pub parts: Vec<(String, CarPart)>, // Model node names and what they are - nodes can also tag themselves with glTF extras
+    pub body: BodySpec,
+    pub lights: LightSpec,
+    pub handling: HandlingSpec,
+    pub steering: Steering,
+    pub aero: Aero,
+    pub engine: Engine,
+    pub suspension: Suspension,
+    pub tire: TireSpec,
+    pub boost: Boost,
+}
+
+/// Role of a node in a car model, read from the spec's `parts` list or the node's glTF extras
+/// (`{"car_part": "FrontWheel"}`) when the model spawns
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CarPart {
+    FrontWheel, // Steers, spins and rides on its own suspension ray
+    RearWheel,
+    Headlight, // Lamps shine along the car's forward axis from the node's position
+    BrakeLight,
+    ReverseLight,
+    Collider, // Scaled cube whose scale gives the body collider's half extents - replaces the spec's box
+}
+
+/// glTF extras payload used to tag model nodes
+#[derive(Deserialize)]
+struct PartExtras {
+    car_part: Option<CarPart>,
+}
+
+/// Rigid body shape and physical properties of the chassis
+#[derive(Deserialize, Clone, Debug)]
+pub struct BodySpec {
+    pub collider_offset: [f32; 3],
+    pub collider_half_extents: [f32; 3],
+    pub mass: f32, // kg
+    pub center_of_mass: [f32; 3], // Relative to the car origin - lower resists rolling over
+    pub principal_inertia: [f32; 3], // kg m^2 about the pitch, yaw and roll axes
+    pub friction: f32, // Body scraping only - the tyre model provides grip
+    pub restitution: f32,
+    pub linear_damping: f32, // Air drag is modelled by car_physics_system, keep this near zero
+    pub angular_damping: f32,
+}
+
+/// Where the lamps sit on the body, in car space
+#[derive(Deserialize, Clone, Debug)]
+pub struct LightSpec {
+    pub headlights: Vec<[f32; 3]>,
+    pub brake_lights: Vec<[f32; 3]>,
+    pub reverse_lights: Vec<[f32; 3]>,
+}
+
+/// Driving feel on top of the engine, suspension and tyre models - mirrors the tuning fields on `Car`
+#[derive(Deserialize, Clone, Debug)]
+pub struct HandlingSpec {
+    pub max_speed: f32,
+    pub brake_force: f32,
+    pub reverse_delay: f32,
+    pub reverse_engage_speed: f32,
+    pub max_reverse_speed: f32,
+    pub front_grip: f32,
+    pub rear_grip: f32,
+    pub handbrake_torque: f32,
+    pub weight_transfer: f32,
+    pub center_of_mass_height: f32,
+    pub drift_threshold: f32,
+    pub rolling_resistance: f32,
+}
+
+impl CarSpec {
+    /// Copy the spec's tuning onto a car, leaving its driving state alone
+    pub fn apply_to(&self, car: &mut Car) {
+        let handling = &self.handling;
+        car.max_speed = handling.max_speed;
+        car.brake_force = handling.brake_force;
+        car.reverse_delay = handling.reverse_delay;
+        car.reverse_engage_speed = handling.reverse_engage_speed;
+        car.max_reverse_speed = handling.max_reverse_speed;
+        car.front_grip = handling.front_grip;
+        car.rear_grip = handling.rear_grip;
+        car.handbrake_torque = handling.handbrake_torque;
+        car.weight_transfer = handling.weight_transfer;
+        car.center_of_mass_height = handling.center_of_mass_height;
+        car.drift_threshold = handling.drift_threshold;
+        car.rolling_resistance = handling.rolling_resistance;
+        car.steering = self.steering;
+        car.aero = self.aero;
+        car.engine = self.engine.clone();
+        car.suspension = self.suspension;
+        car.tire = self.tire;
+        car.boost = self.boost;
+        car.gear = car.gear.clamp(1, car.engine.gear_ratios.len().max(1)); // The new gearbox may have fewer gears
+    }
+
+    pub fn collider(&self) -> Collider {
+        let [x, y, z] = self.body.collider_half_extents;
+        Collider::compound(vec![(Vec3::from(self.body.collider_offset), Quat::IDENTITY, Collider::cuboid(x, y, z))])
+    }
+
+    pub fn mass_properties(&self) -> AdditionalMassProperties {
+        AdditionalMassProperties::MassProperties(MassProperties {
+            local_center_of_mass: Vec3::from(self.body.center_of_mass),
+            mass: self.body.mass,
+            principal_inertia_local_frame: Quat::IDENTITY,
+            principal_inertia: Vec3::from(self.body.principal_inertia),
+        })
+    }
+
+    pub fn damping(&self) -> Damping {
+        Damping { linear_damping: self.body.linear_damping, angular_damping: self.body.angular_damping }
+    }
+}
+
+#[derive(Default)]
+pub struct CarSpecLoader;
+
+#[derive(Debug, Error)]
+pub enum CarSpecLoaderError {
+    #[error("could not read car spec: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse car spec: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for CarSpecLoader {
+    type Asset = CarSpec;
+    type Settings = ();
+    type Error = CarSpecLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<CarSpec, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["car.ron"]
+    }
+}
+
+/// Cars that can be raced - each id names a spec at `cars/<id>.car.ron`
+pub const ROSTER: &[&str] = &["sedan-sports", "hatch-rally"];
+
+/// Specs for every car in the roster, loaded up front so they are ready before the race starts
+#[derive(Resource)]
+pub struct CarRoster {
+    pub cars: Vec<(String, Handle<CarSpec>)>, // (roster id, spec) in menu order
+}
+
+impl CarRoster {
+    pub fn spec(&self, id: &str) -> Option<&Handle<CarSpec>> {
+        self.cars.iter().find(|(car_id, _)| car_id == id).map(|(_, spec)| spec)
+    }
+
+    /// Roster id after `id`, wrapping around - used to cycle cars in the menu
+    pub fn next_id(&self, id: &str) -> Option<&str> {
+        let index = self.cars.iter().position(|(car_id, _)| car_id == id).map_or(0, |index| index + 1);
+        self.cars.get(index % self.cars.len().max(1)).map(|(car_id, _)| car_id.as_str())
+    }
+}
+
+/// Which spec a car was built from - the car follows the file as it is edited
+#[derive(Component)]
+pub struct CarSpecHandle(pub Handle<CarSpec>);
+
+/// Child holding the car's GLB scene
+#[derive(Component)]
+pub struct CarModelScene {
+    pub path: String, // Model the scene was spawned from, so reloads only respawn it when it changes
+    pub instance: Option<InstanceId>, // Scene instance whose parts have been discovered
+    pub collider_hint: Option<Collider>, // Body collider from a `CarPart::Collider` node, overrides the spec's box
+}
+
+/// Lamp built from the spec, rebuilt whenever the spec changes
+#[derive(Component)]
+struct SpecLight;
+
+fn load_car_roster(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let cars = ROSTER
+        .iter()
+        .map(|id| (id.to_string(), asset_server.load(format!("cars/{id}.car.ron"))))
+        .collect();
+    commands.insert_resource(CarRoster { cars });
+}
+
+// Build new cars from their spec, and rebuild live cars whenever a spec file changes on disk
+fn apply_car_specs(
+    mut commands: Commands,
+    mut spec_events: EventReader<AssetEvent<CarSpec>>,
+    specs: Res<Assets<CarSpec>>,
+    asset_server: Res<AssetServer>,
+    mut car_query: Query<(Entity, Ref<CarSpecHandle>, &mut Car, Option<&Children>)>,
+    mut model_query: Query<(&mut SceneRoot, &mut Transform, &mut CarModelScene)>,
+    light_query: Query<(), With<SpecLight>>,
+) {
+    let changed: Vec<AssetId<CarSpec>> = spec_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (entity, spec_handle, mut car, children) in car_query.iter_mut() {
+        // Freshly spawned cars are built as soon as their spec is loaded - it may already be
+        if !spec_handle.is_added() && !changed.contains(&spec_handle.0.id()) {
+            continue;
+        }
+        let Some(spec) = specs.get(&spec_handle.0) else {
+            continue; // Still loading - the Added event will bring us back
+        };
+
+        // Lamps are cheap to rebuild, the model is only respawned when it actually changes
+        let mut has_model = false;
+        let mut collider = spec.collider();
+        for child in children.into_iter().flatten() {
+            if light_query.contains(*child) {
+                commands.entity(*child).despawn();
+            } else if let Ok((mut scene, mut transform, mut model)) = model_query.get_mut(*child) {
+                has_model = true;
+                transform.translation = Vec3::from(spec.model_offset);
+                if model.path != spec.model {
+                    scene.0 = asset_server.load(GltfAssetLabel::Scene(0).from_asset(spec.model.clone()));
+                    *model = CarModelScene { path: spec.model.clone(), instance: None, collider_hint: None };
+                }
+                if let Some(hint) = &model.collider_hint {
+                    collider = hint.clone();
+                }
+            }
+        }
+
+        // A full tank stays full when the spec changes the tank size
+        let full_tank = car.fuel >= car.engine.fuel_capacity;
+        spec.apply_to(&mut car);
+        car.fuel = if full_tank { car.engine.fuel_capacity } else { car.fuel.min(car.engine.fuel_capacity) };
+        commands.entity(entity).insert((
+            collider,
+            spec.mass_properties(),
+            Friction::coefficient(spec.body.friction),
+            Restitution::coefficient(spec.body.restitution),
+            spec.damping(),
+        ));
+
+        commands.entity(entity).with_children(|parent| {
+            if !has_model {
+                parent.spawn((
+                    SceneRoot(asset_server.load(GltfAssetLabel::Scene(0).from_asset(spec.model.clone()))),
+                    Transform::from_translation(Vec3::from(spec.model_offset)),
+                    CarModelScene { path: spec.model.clone(), instance: None, collider_hint: None },
+                ));
+            }
+            spawn_spec_lights(parent, &spec.lights);
+        });
+
+        println!("Applied car spec: {}", spec.name);
+    }
+}
+
+// Once a car's model has spawned, find its wheels, lamps and collider hint by node name or glTF extras
+fn discover_car_parts(
+    mut commands: Commands,
+    scene_spawner: Res<SceneSpawner>,
+    specs: Res<Assets<CarSpec>>,
+    car_query: Query<&CarSpecHandle>,
+    mut model_query: Query<(Entity, &SceneInstance, &ChildOf, &Transform, &mut CarModelScene)>,
+    children: Query<&Children>,
+    nodes: Query<(&Transform, Option<&Name>, Option<&GltfExtras>)>,
+) {
+    for (scene_entity, instance, child_of, scene_transform, mut model) in model_query.iter_mut() {
+        if model.instance == Some(**instance) || !scene_spawner.instance_is_ready(**instance) {
+            continue;
+        }
+        let car_entity = child_of.parent();
+        let Some(spec) = car_query.get(car_entity).ok().and_then(|handle| specs.get(&handle.0)) else {
+            continue;
+        };
+        model.instance = Some(**instance);
+
+        let mut parts = Vec::new();
+        collect_parts(scene_entity, *scene_transform, spec, &children, &nodes, &mut parts);
+
+        let mut wheels = 0;
+        for (node, part, parent_to_car, local_transform) in parts {
+            let node_to_car = parent_to_car * local_transform;
+            match part {
+                CarPart::FrontWheel | CarPart::RearWheel => {
+                    // Remember where the wheel sits on the car for the suspension
+                    commands.entity(node).insert((
+                        Wheel,
+                        WheelSuspension::new(car_entity, node_to_car.translation, local_transform, parent_to_car),
+                    ));
+                    if part == CarPart::FrontWheel {
+                        commands.entity(node).insert(FrontWheel);
+                    }
+
+                    // The node becomes the hub and its meshes move onto a rim that spins inside it
+                    let axle = (node_to_car.rotation.inverse() * Vec3::X).normalize();
+                    let meshes: Vec<Entity> = children.get(node).map(|children| children.to_vec()).unwrap_or_default();
+                    commands
+                        .spawn((WheelRim { axle, angle: 0.0 }, Transform::default(), Visibility::default(), ChildOf(node)))
+                        .add_children(&meshes);
+                    wheels += 1;
+                }
+                CarPart::Headlight | CarPart::BrakeLight | CarPart::ReverseLight => {
+                    // Undo the node's rotation so the lamp faces along the car
+                    let facing = Transform::from_rotation(node_to_car.rotation.inverse());
+                    commands.entity(node).with_children(|parent| spawn_lamp(parent, part, facing));
+                }
+                CarPart::Collider => {
+                    let half_extents = node_to_car.scale;
+                    let collider = Collider::compound(vec![(
+                        node_to_car.translation,
+                        node_to_car.rotation,
+                        Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
+                    )]);
+                    commands.entity(car_entity).insert(collider.clone());
+                    model.collider_hint = Some(collider);
+                }
+            }
+        }
+
+        if wheels == 0 {
+            println!("Warning: no wheels found on {} - map its wheel nodes in the spec's parts or tag them with glTF extras", spec.name);
+        } else {
+            println!("Found {} wheels on {}", wheels, spec.name);
+        }
+    }
+}
+
+// Walk the model hierarchy collecting (node, part, parent transform in car space, local transform)
+fn collect_parts(
+    entity: Entity,
+    entity_to_car: Transform,
+    spec: &CarSpec,
+    children: &Query<&Children>,
+    nodes: &Query<(&Transform, Option<&Name>, Option<&GltfExtras>)>,
+    parts: &mut Vec<(Entity, CarPart, Transform, Transform)>,
+) {
+    let Ok(entity_children) = children.get(entity) else {
+        return;
+    };
+
+    for child in entity_children.iter() {
+        let Ok((transform, name, extras)) = nodes.get(child) else {
+            continue;
+        };
+
+        // The spec's name mapping wins, otherwise the node may have tagged itself in the modelling tool
+        let named_part = name.and_then(|name| {
+            spec.parts.iter().find(|(node_name, _)| node_name == name.as_str()).map(|(_, part)| *part)
+        });
+        let tagged_part = extras.and_then(|extras| {
+            serde_json::from_str::<PartExtras>(&extras.value).ok().and_then(|extras| extras.car_part)
+        });
+        if let Some(part) = named_part.or(tagged_part) {
+            // A part's subtree belongs to it - GLB mesh children often repeat the node's name
+            parts.push((child, part, entity_to_car, *transform));
+            continue;
+        }
+
+        collect_parts(child, entity_to_car * *transform, spec, children, nodes, parts);
+    }
+}
+
+fn spawn_spec_lights(parent: &mut ChildSpawnerCommands, lights: &LightSpec) {
+    let lamps = [
+        (CarPart::Headlight, &lights.headlights),
+        (CarPart::BrakeLight, &lights.brake_lights),
+        (CarPart::ReverseLight, &lights.reverse_lights),
+    ];
+    for (part, positions) in lamps {
+        for &position in positions {
+            spawn_lamp(parent, part, Transform::from_translation(Vec3::from(position)));
+        }
+    }
+}
+
+// Spot lights shine down -Z, which is the car's forward axis
+fn spawn_lamp(parent: &mut ChildSpawnerCommands, part: CarPart, transform: Transform) {
+    match part {
+        CarPart::Headlight => {
+            parent.spawn((
+                SpotLight {
+                    intensity: 5_000_000.0, // Much brighter headlight
+                    color: Color::srgb(1.0, 1.0, 0.9), // Warm white
+                    shadows_enabled: true,
+                    inner_angle: PI / 8.0, // 22.5 degrees inner cone
+                    outer_angle: PI / 4.0, // 45 degrees outer cone
+                    range: 400.0, // Much longer range for nighttime driving
+                    ..default()
+                },
+                transform,
+                SpecLight,
+            ));
+        }
+        // Tail lights brighten under braking, reverse lights come on with reverse gear
+        CarPart::BrakeLight => {
+            parent.spawn((
+                PointLight {
+                    intensity: 2_000.0,
+                    color: Color::srgb(1.0, 0.05, 0.05), // Brake red
+                    range: 4.0,
+                    ..default()
+                },
+                transform,
+                BrakeLight,
+                SpecLight,
+            ));
+        }
+        CarPart::ReverseLight => {
+            parent.spawn((
+                PointLight {
+                    intensity: 0.0,
+                    color: Color::srgb(1.0, 1.0, 1.0),
+                    range: 6.0,
+                    ..default()
+                },
+                transform,
+                ReverseLight,
+                SpecLight,
+            ));
+        }
+        CarPart::FrontWheel | CarPart::RearWheel | CarPart::Collider => {}
+    }
+}
@@ -0,0 +1,164 @@
+use crate::*;
+use crate::menu::GameState;
+use crate::car::Car;
+use crate::input::CarInput;
+use crate::simulation::CarSimulationSet;
+use crate::surface::SurfaceType;
+use bevy_rapier3d::prelude::ContactForceEvent;
+use serde::{Deserialize, Serialize};
+
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DamageConfig>()
+            .add_systems(FixedUpdate, (
+                collision_damage_system,
+                repair_system,
+            ).in_set(CarSimulationSet).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Area of the car an impact lands on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DamageZone {
+    Front,
+    Rear,
+    Left,
+    Right,
+}
+
+/// Accumulated damage per zone, 0.0 pristine to 1.0 wrecked
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CarDamage {
+    pub front: f32, // Engine bay - costs power
+    pub rear: f32,
+    pub left: f32,
+    pub right: f32,
+    pub wheels: f32, // Bent suspension - knocks the steering out of line
+    pub alignment: f32, // Steering pull in input units, positive pulls right
+}
+
+impl CarDamage {
+    pub fn zone(&self, zone: DamageZone) -> f32 {
+        match zone {
+            DamageZone::Front => self.front,
+            DamageZone::Rear => self.rear,
+            DamageZone::Left => self.left,
+            DamageZone::Right => self.right,
+        }
+    }
+
+    fn zone_mut(&mut self, zone: DamageZone) -> &mut f32 {
+        match zone {
+            DamageZone::Front => &mut self.front,
+            DamageZone::Rear => &mut self.rear,
+            DamageZone::Left => &mut self.left,
+            DamageZone::Right => &mut self.right,
+        }
+    }
+
+    /// Overall condition for the HUD, averaged over the body zones and wheels
+    pub fn total(&self) -> f32 {
+        (self.front + self.rear + self.left + self.right + self.wheels) / 5.0
+    }
+
+    /// Share of engine torque still reaching the wheels
+    pub fn power_scale(&self) -> f32 {
+        (1.0 - self.front * 0.5 - self.rear * 0.15).max(0.3) // The engine sits up front
+    }
+
+    /// Extra air drag from crumpled panels, which pulls top speed down
+    pub fn drag_scale(&self) -> f32 {
+        1.0 + (self.front + self.rear + self.left + self.right) * 0.15 + self.wheels * 0.3
+    }
+
+    /// Apply a hit of `amount` to a zone - side hits also bend the suspension on that side
+    pub fn apply_hit(&mut self, zone: DamageZone, amount: f32, config: &DamageConfig) {
+        let value = self.zone_mut(zone);
+        *value = (*value + amount).min(1.0);
+
+        let pull = match zone {
+            DamageZone::Left => -1.0,
+            DamageZone::Right => 1.0,
+            DamageZone::Front | DamageZone::Rear => 0.0,
+        };
+        if pull != 0.0 {
+            self.wheels = (self.wheels + amount * config.wheel_share).min(1.0);
+            self.alignment = (self.alignment + pull * amount * config.wheel_share * config.max_alignment)
+                .clamp(-config.max_alignment, config.max_alignment);
+        }
+    }
+}
+
+/// Zone facing `impact_direction`, given in the car's local space (forward is -Z)
+pub fn impact_zone(impact_direction: Vec3) -> DamageZone {
+    if impact_direction.z.abs() >= impact_direction.x.abs() {
+        if impact_direction.z < 0.0 { DamageZone::Front } else { DamageZone::Rear }
+    } else if impact_direction.x < 0.0 {
+        DamageZone::Left
+    } else {
+        DamageZone::Right
+    }
+}
+
+/// How hard the car has to be hit before it takes damage, and how fast damage builds
+#[derive(Resource, Clone)]
+pub struct DamageConfig {
+    pub min_force: f32, // Contact force (N) below which impacts only scratch the paint
+    pub full_damage_force: f32, // Force above min_force that wrecks a zone in one physics step
+    pub wheel_share: f32, // Fraction of a side hit that goes into the wheels
+    pub max_alignment: f32, // Strongest steering pull wrecked suspension can cause
+}
+
+impl Default for DamageConfig {
+    fn default() -> Self {
+        Self {
+            min_force: 50000.0, // Above the rumble threshold so nudges stay harmless
+            full_damage_force: 4_000_000.0, // A 20 m/s wall hit takes roughly a quarter of a zone
+            wheel_share: 0.5,
+            max_alignment: 0.15,
+        }
+    }
+}
+
+fn collision_damage_system(
+    mut contact_events: EventReader<ContactForceEvent>,
+    config: Res<DamageConfig>,
+    ground_query: Query<(), With<SurfaceType>>,
+    mut car_query: Query<(&Transform, &mut CarDamage)>,
+) {
+    for event in contact_events.read() {
+        if event.max_force_magnitude < config.min_force {
+            continue;
+        }
+
+        // Force direction points from collider1 towards collider2, so it faces the impact for collider1
+        for (car_entity, other, towards_impact) in [
+            (event.collider1, event.collider2, event.max_force_direction),
+            (event.collider2, event.collider1, -event.max_force_direction),
+        ] {
+            // Scraping along the ground is the suspension's job, not damage
+            if ground_query.contains(other) {
+                continue;
+            }
+            let Ok((transform, mut damage)) = car_query.get_mut(car_entity) else {
+                continue;
+            };
+
+            let local_direction = transform.rotation.inverse() * towards_impact;
+            let zone = impact_zone(local_direction);
+            let amount = (event.max_force_magnitude - config.min_force) / config.full_damage_force;
+            damage.apply_hit(zone, amount, &config);
+        }
+    }
+}
+
+fn repair_system(mut car_query: Query<(&CarInput, &mut CarDamage), With<Car>>) {
+    for (input, mut damage) in car_query.iter_mut() {
+        if input.repair && *damage != CarDamage::default() {
+            *damage = CarDamage::default();
+            println!("Car repaired");
+        }
+    }
+}
@@ -0,0 +1,421 @@
+use crate::*;
+use crate::menu::{GameState, GameSettings};
+use crate::car::{CameraTarget, WheelRim, WheelSuspension};
+use crate::car_spec::{CarRoster, CarSpec};
+use crate::lap_timing::{LapCompleted, LapTimer};
+use crate::save_file::{SaveFileError, load_ron, save_ron};
+use crate::time_trial::PersonalBestSet;
+use crate::track_path::{ActiveTrackPath, TrackPath};
+use crate::world::GameEntity;
+use bevy::gltf::GltfAssetLabel;
+use bevy::pbr::NotShadowCaster;
+use bevy::scene::{SceneInstance, SceneSpawner};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GhostConfig>()
+            .add_systems(OnEnter(GameState::InGame), start_ghosts)
+            .add_systems(OnExit(GameState::InGame), end_ghosts)
+            .add_systems(FixedLast, record_ghost_system.run_if(in_state(GameState::InGame)))
+            .add_systems(Update, (
+                load_best_ghost,
+                spawn_ghost_car,
+                ghost_model_system,
+                ghost_playback_system,
+            ).chain().run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Which recorded lap the ghost car replays, picked in Settings
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum GhostSource {
+    Off,
+    #[default]
+    PersonalBest, // Saved with each new time trial personal best for the track and car
+    LastLap, // The lap just driven, counted or not
+    Imported, // Read from `GhostConfig::import_path` when the session starts
+}
+
+impl GhostSource {
+    pub fn next(self) -> Self {
+        match self {
+            GhostSource::Off => GhostSource::PersonalBest,
+            GhostSource::PersonalBest => GhostSource::LastLap,
+            GhostSource::LastLap => GhostSource::Imported,
+            GhostSource::Imported => GhostSource::Off,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GhostSource::Off => "OFF",
+            GhostSource::PersonalBest => "PERSONAL BEST",
+            GhostSource::LastLap => "LAST LAP",
+            GhostSource::Imported => "IMPORTED",
+        }
+    }
+}
+
+/// Where ghost laps are kept and how they look
+#[derive(Resource, Clone)]
+pub struct GhostConfig {
+    pub directory: PathBuf, // Personal best ghosts, one file per track and car
+    pub import_path: PathBuf, // Drop a `.ghost.ron` here to race it
+    pub opacity: f32, // Alpha the ghost's materials are drawn with
+    pub max_frames: usize, // A lap longer than this stops recording rather than growing forever
+}
+
+impl Default for GhostConfig {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("saves/ghosts"),
+            import_path: PathBuf::from("ghosts/import.ghost.ron"),
+            opacity: 0.35,
+            max_frames: 36_000, // Ten minutes at 60 ticks per second
+        }
+    }
+}
+
+/// A pose as plain arrays, so ghost files don't depend on how the math types serialize
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GhostPose {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+}
+
+impl From<Transform> for GhostPose {
+    fn from(transform: Transform) -> Self {
+        Self { translation: transform.translation.into(), rotation: transform.rotation.into() }
+    }
+}
+
+impl GhostPose {
+    pub fn transform(self) -> Transform {
+        Transform::from_translation(Vec3::from(self.translation)).with_rotation(Quat::from_array(self.rotation))
+    }
+
+    fn lerp(self, other: GhostPose, t: f32) -> Transform {
+        let (from, to) = (self.transform(), other.transform());
+        Transform::from_translation(from.translation.lerp(to.translation, t)).with_rotation(from.rotation.slerp(to.rotation, t))
+    }
+}
+
+/// The car and its wheels at the end of one simulation tick
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GhostFrame {
+    pub body: GhostPose,
+    pub wheels: Vec<GhostPose>, // Each wheel node relative to its parent, spin included, in `GhostLap::wheels` order
+}
+
+/// One lap recorded tick by tick, from the start line to the finish
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GhostLap {
+    pub track: String, // Track path name
+    pub car: String, // Roster id - the ghost is drawn with this car's model
+    pub time: f32,
+    pub tick: f32, // Seconds between frames
+    pub wheels: Vec<String>, // Wheel node names in the model
+    pub frames: Vec<GhostFrame>,
+}
+
+impl GhostLap {
+    /// None when there's no file - no ghost has been saved there yet
+    pub fn load(path: &Path) -> Result<Option<Self>, SaveFileError> {
+        load_ron(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SaveFileError> {
+        // One frame per line keeps the file readable without spreading every float over its own line
+        save_ron(self, path, ron::ser::PrettyConfig::default().depth_limit(2))
+    }
+
+    /// Car and wheel poses `time` seconds into the lap, blended between the ticks either side. None past the last frame
+    pub fn sample(&self, time: f32) -> Option<(Transform, Vec<Transform>)> {
+        let position = time.max(0.0) / self.tick.max(f32::EPSILON);
+        let index = position as usize;
+        let from = self.frames.get(index)?;
+        let to = self.frames.get(index + 1).unwrap_or(from);
+        let t = position.fract();
+
+        let wheels = from.wheels.iter().zip(&to.wheels).map(|(from, to)| from.lerp(*to, t)).collect();
+        Some((from.body.lerp(to.body, t), wheels))
+    }
+}
+
+/// File a track and car's personal best ghost is saved to
+pub fn ghost_path(directory: &Path, track: &str, car: &str) -> PathBuf {
+    let track: String = track
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    directory.join(format!("{}_{}.ghost.ron", track, car))
+}
+
+/// This session's ghost laps
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Ghosts {
+    pub track: String, // Track path name, known once the path has loaded
+    pub car: String, // Roster id the player is driving
+    pub recording: GhostLap, // The lap being driven
+    pub last: Option<GhostLap>,
+    pub best: Option<GhostLap>,
+    pub imported: Option<GhostLap>,
+    best_loaded: bool,
+}
+
+impl Ghosts {
+    /// The lap the ghost car replays - an imported lap only when it was driven on this track
+    pub fn shown(&self, source: GhostSource) -> Option<&GhostLap> {
+        let lap = match source {
+            GhostSource::Off => None,
+            GhostSource::PersonalBest => self.best.as_ref(),
+            GhostSource::LastLap => self.last.as_ref(),
+            GhostSource::Imported => self.imported.as_ref().filter(|lap| lap.track == self.track),
+        };
+        lap.filter(|lap| !lap.frames.is_empty())
+    }
+}
+
+/// Translucent replay of a recorded lap. It has no body or collider, so nothing can touch it
+#[derive(Component)]
+pub struct GhostCar {
+    pub car: String, // Roster id of the model shown
+    pub wheel_names: Vec<String>,
+    pub wheels: Vec<Option<Entity>>, // Model nodes for `wheel_names`, found once the scene spawns
+}
+
+/// Child holding a ghost car's GLB scene until its materials have been swapped
+#[derive(Component)]
+struct GhostModel;
+
+fn start_ghosts(mut commands: Commands, config: Res<GhostConfig>, settings: Res<GameSettings>) {
+    let imported = GhostLap::load(&config.import_path).unwrap_or_else(|error| {
+        println!("Warning: {}", error);
+        None
+    });
+    if let Some(lap) = &imported {
+        println!("Imported ghost: {} on {}", lap.car, lap.track);
+    }
+    commands.insert_resource(Ghosts { car: settings.car.clone(), imported, ..default() });
+}
+
+fn end_ghosts(mut commands: Commands) {
+    commands.remove_resource::<Ghosts>();
+}
+
+// The personal best ghost lives on disk per track and car, so wait for the track path to know which
+fn load_best_ghost(
+    config: Res<GhostConfig>,
+    mut ghosts: ResMut<Ghosts>,
+    active_path: Option<Res<ActiveTrackPath>>,
+    paths: Res<Assets<TrackPath>>,
+) {
+    if ghosts.best_loaded {
+        return;
+    }
+    let Some(path) = active_path.and_then(|active_path| paths.get(&active_path.0)) else {
+        return; // Still loading
+    };
+    ghosts.track = path.name.clone();
+    ghosts.best_loaded = true;
+    ghosts.best = GhostLap::load(&ghost_path(&config.directory, &ghosts.track, &ghosts.car)).unwrap_or_else(|error| {
+        println!("Warning: {}", error);
+        None
+    });
+}
+
+// Runs after the lap has been judged, so a new personal best is known about the tick it is set
+#[allow(clippy::too_many_arguments)]
+fn record_ghost_system(
+    config: Res<GhostConfig>,
+    time: Res<Time>,
+    mut ghosts: ResMut<Ghosts>,
+    mut lap_events: EventReader<LapCompleted>,
+    mut best_events: EventReader<PersonalBestSet>,
+    car_query: Query<(Entity, &Transform, &LapTimer), With<CameraTarget>>,
+    wheel_query: Query<(Entity, &WheelSuspension, &Name, &Transform)>,
+    rim_query: Query<(&Transform, &ChildOf), With<WheelRim>>,
+) {
+    let Ok((entity, transform, timer)) = car_query.single() else {
+        return;
+    };
+
+    for lap in lap_events.read().filter(|lap| lap.car == entity) {
+        let mut recorded = std::mem::take(&mut ghosts.recording);
+        recorded.time = lap.time;
+        ghosts.last = Some(recorded);
+    }
+    for _ in best_events.read().filter(|best| best.car == entity) {
+        let Some(lap) = ghosts.last.clone() else {
+            continue;
+        };
+        let path = ghost_path(&config.directory, &lap.track, &lap.car);
+        match lap.save(&path) {
+            Ok(()) => println!("Saved ghost to {}", path.display()),
+            Err(error) => println!("Warning: {}", error),
+        }
+        ghosts.best = Some(lap);
+    }
+
+    if !timer.started {
+        return;
+    }
+    if timer.current == 0.0 {
+        // Crossed the line this tick - the next lap's recording starts here
+        ghosts.recording = GhostLap { track: ghosts.track.clone(), car: ghosts.car.clone(), tick: time.delta_secs(), ..default() };
+    }
+    if ghosts.recording.frames.len() >= config.max_frames {
+        return;
+    }
+
+    // Hub pose with the rim's spin folded in, so the plain model's wheel nodes can show both
+    let mut wheels: Vec<(&str, GhostPose)> = wheel_query
+        .iter()
+        .filter(|(_, wheel, _, _)| wheel.car == entity)
+        .map(|(wheel_entity, _, name, hub)| {
+            let spin = rim_query
+                .iter()
+                .find(|(_, child_of)| child_of.parent() == wheel_entity)
+                .map_or(Quat::IDENTITY, |(rim, _)| rim.rotation);
+            (name.as_str(), hub.with_rotation(hub.rotation * spin).into())
+        })
+        .collect();
+    wheels.sort_by(|a, b| a.0.cmp(b.0));
+
+    let recording = &mut ghosts.recording;
+    if recording.frames.is_empty() {
+        recording.wheels = wheels.iter().map(|(name, _)| name.to_string()).collect();
+    }
+    recording.frames.push(GhostFrame {
+        body: (*transform).into(),
+        wheels: wheels.into_iter().map(|(_, pose)| pose).collect(),
+    });
+}
+
+// Keep one ghost car matching the chosen lap - respawned when the lap's car or wheels change
+fn spawn_ghost_car(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    ghosts: Res<Ghosts>,
+    roster: Res<CarRoster>,
+    specs: Res<Assets<CarSpec>>,
+    asset_server: Res<AssetServer>,
+    ghost_query: Query<(Entity, &GhostCar)>,
+) {
+    let lap = ghosts.shown(settings.ghost);
+    let mut current = false;
+    for (entity, ghost) in ghost_query.iter() {
+        if lap.is_some_and(|lap| lap.car == ghost.car && lap.wheels == ghost.wheel_names) {
+            current = true;
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+    let Some(lap) = lap.filter(|_| !current) else {
+        return;
+    };
+    let Some(spec) = roster.spec(&lap.car).and_then(|spec| specs.get(spec)) else {
+        return; // Unknown car, or its spec is still loading
+    };
+
+    commands
+        .spawn((
+            Transform::default(),
+            Visibility::Hidden, // Shown once playback has a pose for it
+            GhostCar { car: lap.car.clone(), wheel_names: lap.wheels.clone(), wheels: Vec::new() },
+            GameEntity, // Mark for cleanup
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                SceneRoot(asset_server.load(GltfAssetLabel::Scene(0).from_asset(spec.model.clone()))),
+                Transform::from_translation(Vec3::from(spec.model_offset)),
+                GhostModel,
+            ));
+        });
+}
+
+// Once a ghost's scene has spawned, make it see-through and find its wheels
+#[allow(clippy::too_many_arguments)]
+fn ghost_model_system(
+    mut commands: Commands,
+    config: Res<GhostConfig>,
+    scene_spawner: Res<SceneSpawner>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    model_query: Query<(Entity, &SceneInstance, &ChildOf), With<GhostModel>>,
+    mut ghost_query: Query<&mut GhostCar>,
+    children: Query<&Children>,
+    node_query: Query<(Option<&Name>, Option<&MeshMaterial3d<StandardMaterial>>)>,
+) {
+    for (model_entity, instance, child_of) in model_query.iter() {
+        if !scene_spawner.instance_is_ready(**instance) {
+            continue;
+        }
+        let Ok(mut ghost) = ghost_query.get_mut(child_of.parent()) else {
+            continue;
+        };
+        commands.entity(model_entity).remove::<GhostModel>();
+
+        // Materials are shared between meshes, so each is copied once
+        let mut ghost_materials: HashMap<AssetId<StandardMaterial>, Handle<StandardMaterial>> = HashMap::new();
+        let mut wheels = vec![None; ghost.wheel_names.len()];
+        for node in children.iter_descendants(model_entity) {
+            let Ok((name, material)) = node_query.get(node) else {
+                continue;
+            };
+            if let Some(index) = name.and_then(|name| ghost.wheel_names.iter().position(|wheel| wheel == name.as_str())) {
+                wheels[index] = Some(node);
+            }
+            let Some(material) = material else {
+                continue;
+            };
+            let handle = ghost_materials.entry(material.id()).or_insert_with(|| {
+                let mut ghost_material = materials.get(&material.0).cloned().unwrap_or_default();
+                ghost_material.base_color.set_alpha(config.opacity);
+                ghost_material.alpha_mode = AlphaMode::Blend;
+                materials.add(ghost_material)
+            });
+            commands.entity(node).insert((MeshMaterial3d(handle.clone()), NotShadowCaster));
+        }
+        ghost.wheels = wheels;
+    }
+}
+
+// Replay the chosen lap level with the player's lap clock, hidden until the player starts a lap and once it has run out
+fn ghost_playback_system(
+    settings: Res<GameSettings>,
+    ghosts: Res<Ghosts>,
+    fixed_time: Res<Time<Fixed>>,
+    player_query: Query<&LapTimer, With<CameraTarget>>,
+    mut ghost_query: Query<(&GhostCar, &mut Transform, &mut Visibility)>,
+    mut node_query: Query<&mut Transform, Without<GhostCar>>,
+) {
+    let Ok(timer) = player_query.single() else {
+        return;
+    };
+    // The player is drawn a fraction of a tick behind the simulation - the ghost matches that
+    let time = timer.current - fixed_time.timestep().as_secs_f32() + fixed_time.overstep().as_secs_f32();
+    let pose = ghosts
+        .shown(settings.ghost)
+        .filter(|_| timer.started)
+        .and_then(|lap| lap.sample(time));
+
+    for (ghost, mut transform, mut visibility) in ghost_query.iter_mut() {
+        let Some((body, wheels)) = &pose else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *transform = *body;
+        *visibility = Visibility::Inherited;
+        for (node, wheel) in ghost.wheels.iter().zip(wheels) {
+            if let Some(mut node_transform) = node.and_then(|node| node_query.get_mut(node).ok()) {
+                node_transform.translation = wheel.translation;
+                node_transform.rotation = wheel.rotation;
+            }
+        }
+    }
+}
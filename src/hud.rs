@@ -0,0 +1,94 @@
+use crate::*;
+use crate::menu::GameState;
+use crate::car::{Car, CameraTarget};
+use crate::assists::DriverAssists;
+use crate::damage::CarDamage;
+use crate::lap_timing::{LapTimer, format_lap_time};
+use crate::time_trial::TimeTrial;
+use crate::world::GameEntity;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::InGame), setup_hud)
+            .add_systems(Update, hud_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+#[derive(Component)]
+pub struct HudText;
+
+fn setup_hud(mut commands: Commands) {
+    // Bottom-left readout for the player's car
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(20.0),
+            bottom: Val::Px(20.0),
+            ..default()
+        },
+        HudText,
+        GameEntity, // Mark for cleanup
+    ));
+}
+
+#[allow(clippy::type_complexity)]
+fn hud_system(
+    car_query: Query<(&Car, &DriverAssists, Option<&CarDamage>, Option<&LapTimer>), With<CameraTarget>>,
+    mut hud_query: Query<(&mut Text, &mut TextColor), With<HudText>>,
+    time_trial: Option<Res<TimeTrial>>,
+) {
+    let Ok((car, assists, damage, lap_timer)) = car_query.single() else {
+        return;
+    };
+    let Ok((mut text, mut color)) = hud_query.single_mut() else {
+        return;
+    };
+
+    let gear = if car.reversing { "R".to_string() } else { car.gear.to_string() };
+    let fuel = if car.fuel > 0.0 { format!("{:.1} L", car.fuel) } else { "EMPTY".to_string() };
+    let damage = damage.map_or(0.0, |damage| damage.total());
+
+    // Only the aids that stepped in this frame
+    let active_assists: Vec<&str> = [
+        (assists.abs_active, "ABS"),
+        (assists.traction_control_active, "TC"),
+        (assists.stability_control_active, "ESC"),
+    ]
+    .into_iter()
+    .filter_map(|(active, label)| active.then_some(label))
+    .collect();
+
+    // Nothing to time until the car first crosses the start line
+    let lap = lap_timer.filter(|timer| timer.started).map_or(String::new(), |timer| {
+        let best = timer.best.map_or("-".to_string(), format_lap_time);
+        let invalid = if timer.missed { "  MISSED CHECKPOINT" } else { "" };
+        // Live gap to the personal best in a time trial, once there is one to compare against
+        let delta = time_trial.as_ref().and_then(|trial| trial.delta).map_or(String::new(), |delta| format!("  DELTA {:+.3}", delta));
+        format!("LAP {}  {}  BEST {}{}{}\n", timer.lap + 1, format_lap_time(timer.current), best, delta, invalid)
+    });
+
+    text.0 = format!(
+        "{}{:.0} km/h  GEAR {}\n{} {:.0}%\nFUEL {}\nTYRES {:.0}%\nDAMAGE {:.0}%\n{}",
+        lap,
+        car.speed.abs() * 3.6,
+        gear,
+        if car.boosting { "BOOST!" } else { "BOOST" },
+        car.boost_meter * 100.0,
+        fuel,
+        (1.0 - car.tire_wear) * 100.0,
+        damage * 100.0,
+        active_assists.join(" "),
+    );
+
+    // Warn when the tank is nearly dry
+    let low_fuel = car.fuel < car.engine.fuel_capacity * 0.1;
+    color.0 = if low_fuel { Color::srgb(1.0, 0.4, 0.3) } else { Color::WHITE };
+}
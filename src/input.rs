@@ -0,0 +1,299 @@
+use crate::*;
+use crate::menu::GameState;
+use bevy::input::InputSystem;
+use bevy::input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest};
+use bevy_rapier3d::prelude::ContactForceEvent;
+use std::time::Duration;
+
+pub struct CarInputPlugin;
+
+impl Plugin for CarInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CarActionMap>()
+            .init_resource::<GamepadConfig>()
+            .init_resource::<PlayerGamepad>()
+            .init_resource::<PendingPresses>()
+            .add_systems(PreUpdate, (
+                player_input_device_system,
+                latch_presses_system,
+            ).chain().after(InputSystem).run_if(in_state(GameState::InGame)))
+            .add_systems(FixedUpdate, (
+                (keyboard_input_system, gamepad_input_system),
+                clear_presses_system,
+            ).chain().in_set(CarInputSet).run_if(in_state(GameState::InGame)))
+            .add_systems(Update, collision_rumble_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Fixed-tick systems that write `CarInput` run in this set; everything that drives a car runs after it
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CarInputSet;
+
+/// Driver commands for one car - the only thing car and camera systems read
+#[derive(Component, Default, Clone, Copy, Debug, PartialEq)]
+pub struct CarInput {
+    pub throttle: f32, // 0.0 to 1.0
+    pub brake: f32, // 0.0 to 1.0
+    pub steer: f32, // -1.0 (full left) to 1.0 (full right)
+    pub handbrake: bool,
+    pub boost: bool, // Held to fire the nitro
+    pub shift_up: bool, // Edge-triggered - true for a single tick per request
+    pub shift_down: bool, // Edge-triggered - true for a single tick per request
+    pub toggle_transmission: bool, // Edge-triggered - swap automatic/manual
+    pub reset: bool, // Edge-triggered - put the car back on its wheels
+    pub repair: bool, // Edge-triggered - fix all damage
+    pub service: bool, // Edge-triggered - refuel and change tyres
+}
+
+/// Which input source is allowed to write a car's `CarInput`
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputSource {
+    Keyboard,
+    Gamepad,
+    Ai,
+    Replay,
+    Network,
+}
+
+/// Configurable key bindings for the keyboard input source
+#[derive(Resource, Clone)]
+pub struct CarActionMap {
+    pub throttle: Vec<KeyCode>,
+    pub brake: Vec<KeyCode>,
+    pub steer_left: Vec<KeyCode>,
+    pub steer_right: Vec<KeyCode>,
+    pub handbrake: Vec<KeyCode>,
+    pub boost: Vec<KeyCode>,
+    pub shift_up: Vec<KeyCode>,
+    pub shift_down: Vec<KeyCode>,
+    pub toggle_transmission: Vec<KeyCode>,
+    pub reset: Vec<KeyCode>,
+    pub repair: Vec<KeyCode>,
+    pub service: Vec<KeyCode>,
+}
+
+impl Default for CarActionMap {
+    fn default() -> Self {
+        Self {
+            throttle: vec![KeyCode::ArrowUp, KeyCode::KeyW],
+            brake: vec![KeyCode::ArrowDown, KeyCode::KeyS],
+            steer_left: vec![KeyCode::ArrowLeft, KeyCode::KeyA],
+            steer_right: vec![KeyCode::ArrowRight, KeyCode::KeyD],
+            handbrake: vec![KeyCode::Space],
+            boost: vec![KeyCode::ShiftLeft],
+            shift_up: vec![KeyCode::KeyE],
+            shift_down: vec![KeyCode::KeyQ],
+            toggle_transmission: vec![KeyCode::KeyM],
+            reset: vec![KeyCode::KeyR],
+            repair: vec![KeyCode::KeyF],
+            service: vec![KeyCode::KeyP],
+        }
+    }
+}
+
+/// Gamepad tuning and bindings for the gamepad input source
+#[derive(Resource, Clone)]
+pub struct GamepadConfig {
+    pub steer_deadzone: f32, // Stick travel ignored around the centre
+    pub steer_exponent: f32, // Response curve - above 1.0 softens small stick movements
+    pub trigger_deadzone: f32,
+    pub handbrake: GamepadButton,
+    pub boost: GamepadButton,
+    pub shift_up: GamepadButton,
+    pub shift_down: GamepadButton,
+    pub toggle_transmission: GamepadButton,
+    pub reset: GamepadButton,
+    pub repair: GamepadButton,
+    pub service: GamepadButton,
+    pub rumble_enabled: bool,
+    pub rumble_force: f32, // Contact force (N) that produces a full-strength rumble
+}
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        Self {
+            steer_deadzone: 0.1,
+            steer_exponent: 1.5,
+            trigger_deadzone: 0.05,
+            handbrake: GamepadButton::South,
+            boost: GamepadButton::RightThumb, // Face buttons are taken - click the right stick
+            shift_up: GamepadButton::RightTrigger, // Shoulder buttons - triggers are pedals
+            shift_down: GamepadButton::LeftTrigger,
+            toggle_transmission: GamepadButton::North,
+            reset: GamepadButton::Select,
+            repair: GamepadButton::West,
+            service: GamepadButton::East,
+            rumble_enabled: true,
+            rumble_force: 200000.0,
+        }
+    }
+}
+
+/// The gamepad the local player last used, if any
+#[derive(Resource, Default)]
+pub struct PlayerGamepad(pub Option<Entity>);
+
+/// Buttons pressed since the last simulation tick. Frames can run with no tick or several, so presses
+/// are collected every frame and each one reaches exactly one tick
+#[derive(Resource, Default)]
+pub struct PendingPresses {
+    pub keys: Vec<KeyCode>,
+    pub buttons: Vec<GamepadButton>, // From the player's gamepad only
+}
+
+/// Rescale an axis so the deadzone maps to zero and full travel to one, then apply the response curve
+pub fn shape_axis(value: f32, deadzone: f32, exponent: f32) -> f32 {
+    let magnitude = ((value.abs() - deadzone) / (1.0 - deadzone).max(f32::EPSILON)).clamp(0.0, 1.0);
+    magnitude.powf(exponent) * value.signum()
+}
+
+fn axis(keyboard_input: &ButtonInput<KeyCode>, keys: &[KeyCode]) -> f32 {
+    if keyboard_input.any_pressed(keys.iter().copied()) { 1.0 } else { 0.0 }
+}
+
+fn latch_presses_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    player_gamepad: Res<PlayerGamepad>,
+    mut pending: ResMut<PendingPresses>,
+) {
+    pending.keys.extend(keyboard_input.get_just_pressed().copied());
+    if let Some(gamepad) = player_gamepad.0.and_then(|entity| gamepads.get(entity).ok()) {
+        pending.buttons.extend(gamepad.get_just_pressed().copied());
+    }
+}
+
+fn clear_presses_system(mut pending: ResMut<PendingPresses>) {
+    pending.keys.clear();
+    pending.buttons.clear();
+}
+
+fn keyboard_input_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    pending: Res<PendingPresses>,
+    action_map: Res<CarActionMap>,
+    mut car_query: Query<(&mut CarInput, &InputSource)>,
+) {
+    let pressed = |keys: &[KeyCode]| keys.iter().any(|key| pending.keys.contains(key));
+
+    for (mut input, source) in car_query.iter_mut() {
+        if *source != InputSource::Keyboard {
+            continue;
+        }
+
+        // Digital keys map to full-scale analog values
+        *input = CarInput {
+            throttle: axis(&keyboard_input, &action_map.throttle),
+            brake: axis(&keyboard_input, &action_map.brake),
+            steer: axis(&keyboard_input, &action_map.steer_right) - axis(&keyboard_input, &action_map.steer_left),
+            handbrake: keyboard_input.any_pressed(action_map.handbrake.iter().copied()),
+            boost: keyboard_input.any_pressed(action_map.boost.iter().copied()),
+            shift_up: pressed(&action_map.shift_up),
+            shift_down: pressed(&action_map.shift_down),
+            toggle_transmission: pressed(&action_map.toggle_transmission),
+            reset: pressed(&action_map.reset),
+            repair: pressed(&action_map.repair),
+            service: pressed(&action_map.service),
+        };
+    }
+}
+
+// Hand the local player's car to whichever device was touched last
+fn player_input_device_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    config: Res<GamepadConfig>,
+    mut player_gamepad: ResMut<PlayerGamepad>,
+    mut car_query: Query<&mut InputSource>,
+) {
+    let active_gamepad = gamepads.iter().find(|(_, gamepad)| {
+        gamepad.get_just_pressed().next().is_some()
+            || gamepad.left_stick().x.abs() > config.steer_deadzone
+            || gamepad.get(GamepadButton::RightTrigger2).unwrap_or(0.0) > config.trigger_deadzone
+            || gamepad.get(GamepadButton::LeftTrigger2).unwrap_or(0.0) > config.trigger_deadzone
+    });
+
+    let new_source = if let Some((entity, _)) = active_gamepad {
+        player_gamepad.0 = Some(entity);
+        InputSource::Gamepad
+    } else if keyboard_input.get_just_pressed().next().is_some() {
+        InputSource::Keyboard
+    } else {
+        return;
+    };
+
+    // Only player-driven cars switch - AI, replay and network cars keep their source
+    for mut source in car_query.iter_mut() {
+        if matches!(*source, InputSource::Keyboard | InputSource::Gamepad) && *source != new_source {
+            *source = new_source;
+        }
+    }
+}
+
+fn gamepad_input_system(
+    gamepads: Query<&Gamepad>,
+    config: Res<GamepadConfig>,
+    player_gamepad: Res<PlayerGamepad>,
+    pending: Res<PendingPresses>,
+    mut car_query: Query<(&mut CarInput, &InputSource)>,
+) {
+    let Some(gamepad) = player_gamepad.0.and_then(|entity| gamepads.get(entity).ok()) else {
+        return;
+    };
+    let pressed = |button| pending.buttons.contains(&button);
+
+    for (mut input, source) in car_query.iter_mut() {
+        if *source != InputSource::Gamepad {
+            continue;
+        }
+
+        // Left stick steers, triggers are throttle and brake pedals
+        let trigger = |button| shape_axis(gamepad.get(button).unwrap_or(0.0), config.trigger_deadzone, 1.0);
+        *input = CarInput {
+            throttle: trigger(GamepadButton::RightTrigger2),
+            brake: trigger(GamepadButton::LeftTrigger2),
+            steer: shape_axis(gamepad.left_stick().x, config.steer_deadzone, config.steer_exponent),
+            handbrake: gamepad.pressed(config.handbrake),
+            boost: gamepad.pressed(config.boost),
+            shift_up: pressed(config.shift_up),
+            shift_down: pressed(config.shift_down),
+            toggle_transmission: pressed(config.toggle_transmission),
+            reset: pressed(config.reset),
+            repair: pressed(config.repair),
+            service: pressed(config.service),
+        };
+    }
+}
+
+fn collision_rumble_system(
+    mut contact_events: EventReader<ContactForceEvent>,
+    config: Res<GamepadConfig>,
+    player_gamepad: Res<PlayerGamepad>,
+    car_query: Query<&InputSource>,
+    mut rumble_requests: EventWriter<GamepadRumbleRequest>,
+) {
+    let Some(gamepad) = player_gamepad.0 else {
+        contact_events.clear();
+        return;
+    };
+
+    for event in contact_events.read() {
+        let hit_pad_car = [event.collider1, event.collider2]
+            .iter()
+            .any(|entity| car_query.get(*entity).is_ok_and(|source| *source == InputSource::Gamepad));
+        if !config.rumble_enabled || !hit_pad_car {
+            continue;
+        }
+
+        // Harder impacts rumble harder, big ones kick in the heavy motor too
+        let strength = (event.total_force_magnitude / config.rumble_force).clamp(0.0, 1.0);
+        rumble_requests.write(GamepadRumbleRequest::Add {
+            gamepad,
+            intensity: GamepadRumbleIntensity {
+                strong_motor: (strength - 0.5).max(0.0) * 2.0,
+                weak_motor: strength,
+            },
+            duration: Duration::from_millis(150),
+        });
+    }
+}
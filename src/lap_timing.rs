@@ -0,0 +1,216 @@
+use crate::*;
+use crate::menu::GameState;
+use crate::recovery::CarRecovery;
+use crate::track_path::{ActiveTrackPath, TrackPath};
+use crate::world::GameEntity;
+use bevy_rapier3d::prelude::*;
+
+pub struct LapTimingPlugin;
+
+impl Plugin for LapTimingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CheckpointPassed>()
+            .add_event::<LapCompleted>()
+            .add_systems(Update, spawn_checkpoints.run_if(in_state(GameState::InGame)))
+            .add_systems(FixedUpdate, lap_timing_system
+                .after(PhysicsSet::Writeback)
+                .run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Sensor gate across the road. Index 0 is the start/finish line, the rest are checkpoints in driving order
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Checkpoint {
+    pub index: usize,
+    pub ground: Transform, // Middle of the road under the gate, facing the driving direction
+}
+
+/// A car's progress round the lap and the times it has set
+#[derive(Component, Clone, Debug, Default)]
+pub struct LapTimer {
+    pub started: bool, // Crossed the start line - the clock runs from then on
+    pub lap: u32, // Valid laps completed
+    pub current: f32, // Seconds into the lap being driven
+    pub last: Option<f32>, // Last valid lap
+    pub best: Option<f32>,
+    pub next_checkpoint: usize, // Gate expected next, 0 once every checkpoint is done and the finish is due
+    pub missed: bool, // A checkpoint was skipped, so this lap won't count
+    pub splits: Vec<f32>, // Lap time at each checkpoint passed this lap
+    pub last_sectors: Vec<f32>, // Sector times of the last valid lap
+    pub best_sectors: Vec<f32>, // Sector times of the best lap
+}
+
+impl LapTimer {
+    /// Time since the last gate, or since the start of the lap
+    pub fn sector_time(&self) -> f32 {
+        self.current - self.splits.last().copied().unwrap_or(0.0)
+    }
+
+    /// Sector times of the current lap so far, from its splits
+    pub fn sectors(&self) -> Vec<f32> {
+        let mut previous = 0.0;
+        self.splits
+            .iter()
+            .map(|&split| {
+                let sector = split - previous;
+                previous = split;
+                sector
+            })
+            .collect()
+    }
+
+    fn start_lap(&mut self, checkpoints: usize) {
+        self.started = true;
+        self.current = 0.0;
+        self.next_checkpoint = if checkpoints > 0 { 1 } else { 0 };
+        self.missed = false;
+        self.splits.clear();
+    }
+}
+
+/// Sent when a car drives through a checkpoint gate
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CheckpointPassed {
+    pub car: Entity,
+    pub checkpoint: usize, // Gate index, 1 for the first checkpoint after the start line
+    pub split: f32, // Lap time at the gate
+    pub sector: f32, // Time since the previous gate
+    pub missed: bool, // Gates were skipped to get here this lap
+}
+
+/// Sent when a car crosses the finish line at the end of a lap, counted or not
+#[derive(Event, Clone, Debug)]
+pub struct LapCompleted {
+    pub car: Entity,
+    pub lap: u32, // Valid laps the car has done, including this one if it counts
+    pub time: f32,
+    pub sectors: Vec<f32>, // One per gate passed, ending with the run to the line
+    pub valid: bool, // False if a checkpoint was missed - the lap isn't counted
+    pub best: bool, // Quickest valid lap so far
+}
+
+/// Lap time as `m:ss.sss`
+pub fn format_lap_time(seconds: f32) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u32;
+    format!("{}:{:02}.{:03}", millis / 60_000, millis / 1000 % 60, millis % 1000)
+}
+
+// Build the gates from the active track path, and rebuild them whenever the path file changes
+fn spawn_checkpoints(
+    mut commands: Commands,
+    active_path: Option<Res<ActiveTrackPath>>,
+    paths: Res<Assets<TrackPath>>,
+    mut path_events: EventReader<AssetEvent<TrackPath>>,
+    gate_query: Query<Entity, With<Checkpoint>>,
+) {
+    let Some(active_path) = active_path else {
+        return;
+    };
+    let modified = path_events.read().any(|event| event.is_modified(&active_path.0));
+    if !gate_query.is_empty() && !modified {
+        return;
+    }
+    let Some(path) = paths.get(&active_path.0) else {
+        return; // Still loading
+    };
+
+    for entity in gate_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    for (index, distance) in path.gate_distances().into_iter().enumerate() {
+        let sample = path.sample(distance);
+        let ground = Transform::from_translation(sample.position).looking_to(sample.direction, Vec3::Y);
+        // Wider than the road so cars cutting the verge still count, tall enough for a car mid-jump
+        commands.spawn((
+            Transform::from_translation(sample.position + Vec3::Y * 1.5).with_rotation(ground.rotation),
+            Collider::cuboid(sample.width * 0.5 + 2.0, 2.0, 0.5),
+            Sensor,
+            ActiveEvents::COLLISION_EVENTS,
+            Checkpoint { index, ground },
+            GameEntity, // Mark for cleanup
+        ));
+    }
+    println!("Lap timing: {} checkpoints on {}", path.checkpoints.len(), path.name);
+}
+
+fn lap_timing_system(
+    time: Res<Time>,
+    mut collision_events: EventReader<CollisionEvent>,
+    gate_query: Query<&Checkpoint>,
+    mut car_query: Query<(&Velocity, &mut LapTimer, Option<&mut CarRecovery>)>,
+    mut checkpoint_events: EventWriter<CheckpointPassed>,
+    mut lap_events: EventWriter<LapCompleted>,
+) {
+    let dt = time.delta_secs();
+    for (_, mut timer, _) in car_query.iter_mut() {
+        if timer.started {
+            timer.current += dt;
+        }
+    }
+    let checkpoints = gate_query.iter().count().saturating_sub(1);
+
+    for event in collision_events.read() {
+        let CollisionEvent::Started(first, second, _) = *event else {
+            continue;
+        };
+        let (gate, car) = if gate_query.contains(first) { (first, second) } else { (second, first) };
+        let (Ok(checkpoint), Ok((velocity, mut timer, recovery))) = (gate_query.get(gate), car_query.get_mut(car)) else {
+            continue;
+        };
+
+        // Only driving forwards into the gate counts - not reversing into it or being reset onto it
+        if velocity.linvel.dot(*checkpoint.ground.forward()) <= 0.0 {
+            continue;
+        }
+        if let Some(mut recovery) = recovery {
+            let ride_height = recovery.ride_height;
+            recovery.checkpoint = Some(checkpoint.ground.with_translation(checkpoint.ground.translation + Vec3::Y * ride_height));
+        }
+
+        if checkpoint.index == 0 {
+            if timer.started {
+                finish_lap(car, &mut timer, checkpoints, &mut lap_events);
+            }
+            timer.start_lap(checkpoints);
+            continue;
+        }
+        if !timer.started {
+            continue;
+        }
+
+        // Gates behind the one expected are already done - ones further on mean some were skipped
+        if checkpoint.index < timer.next_checkpoint || timer.next_checkpoint == 0 {
+            continue;
+        }
+        if checkpoint.index > timer.next_checkpoint {
+            timer.missed = true;
+        }
+        let sector = timer.sector_time();
+        let split = timer.current;
+        timer.splits.push(split);
+        timer.next_checkpoint = (checkpoint.index + 1) % (checkpoints + 1);
+        checkpoint_events.write(CheckpointPassed { car, checkpoint: checkpoint.index, split, sector, missed: timer.missed });
+    }
+}
+
+fn finish_lap(car: Entity, timer: &mut LapTimer, checkpoints: usize, lap_events: &mut EventWriter<LapCompleted>) {
+    let time = timer.current;
+    let mut sectors = timer.sectors();
+    sectors.push(timer.sector_time());
+
+    let valid = !timer.missed && timer.next_checkpoint == 0 && timer.splits.len() == checkpoints;
+    let best = valid && timer.best.is_none_or(|best| time < best);
+    if valid {
+        timer.lap += 1;
+        timer.last = Some(time);
+        timer.last_sectors = sectors.clone();
+        if best {
+            timer.best = Some(time);
+            timer.best_sectors = sectors.clone();
+        }
+        println!("Lap {}: {}{}", timer.lap, format_lap_time(time), if best { " - best" } else { "" });
+    } else {
+        println!("Lap invalid: missed a checkpoint");
+    }
+    lap_events.write(LapCompleted { car, lap: timer.lap, time, sectors, valid, best });
+}
@@ -1,4 +1,21 @@
 pub mod car;
+pub mod car_spec;
+pub mod tire;
+pub mod surface;
+pub mod assists;
+pub mod recovery;
+pub mod damage;
+pub mod boost;
+pub mod simulation;
+pub mod telemetry;
+pub mod track_path;
+pub mod lap_timing;
+pub mod ai;
+pub mod save_file;
+pub mod time_trial;
+pub mod ghost;
+pub mod hud;
+pub mod input;
 pub mod camera;
 pub mod lighting;
 pub mod world;
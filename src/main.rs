@@ -1,7 +1,21 @@
 use bevy::prelude::*;
-use bevy_rapier3d::prelude::*;
 use bevy_vibes::{
     car::CarPlugin,
+    car_spec::CarSpecPlugin,
+    surface::SurfacePlugin,
+    assists::AssistsPlugin,
+    recovery::RecoveryPlugin,
+    damage::DamagePlugin,
+    boost::BoostPlugin,
+    simulation::SimulationPlugin,
+    telemetry::TelemetryPlugin,
+    track_path::TrackPathPlugin,
+    lap_timing::LapTimingPlugin,
+    ai::AiPlugin,
+    time_trial::TimeTrialPlugin,
+    ghost::GhostPlugin,
+    hud::HudPlugin,
+    input::CarInputPlugin,
     camera::CameraPlugin,
     lighting::LightingPlugin,
     world::WorldPlugin,
@@ -14,13 +28,23 @@ use bevy_vibes::menu::*;
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
-        .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+        .add_plugins(SimulationPlugin) // Fixed-tick car simulation and rapier stepping
+        .add_plugins(TelemetryPlugin) // Records every tick - F8 exports
+        .add_plugins((TrackPathPlugin, LapTimingPlugin, AiPlugin, TimeTrialPlugin, GhostPlugin)) // Track layout, lap times, the opponents driving it, the clock and the ghost
         // .add_plugins(RapierDebugRenderPlugin::default()) // Add debug wireframes for physics
         .insert_resource(ClearColor(Color::srgb(0.1, 0.1, 0.2))) // Dark menu background
         .add_plugins((
             MenuPlugin,
             WorldPlugin,
+            CarInputPlugin,
             CarPlugin,
+            CarSpecPlugin,
+            SurfacePlugin,
+            AssistsPlugin,
+            RecoveryPlugin,
+            DamagePlugin,
+            BoostPlugin,
+            HudPlugin,
             CameraPlugin,
             LightingPlugin,
             AtmospherePlugin,
@@ -36,12 +60,13 @@ fn main() {
 
 fn handle_game_input(
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
     mut next_state: ResMut<NextState<GameState>>,
     settings: Res<GameSettings>,
     mut camera_query: Query<&mut MotionBlur, With<Camera3d>>,
 ) {
-    // ESC to return to main menu
-    if keyboard_input.just_pressed(KeyCode::Escape) {
+    // ESC or Start to return to main menu
+    if keyboard_input.just_pressed(KeyCode::Escape) || gamepads.iter().any(|gamepad| gamepad.just_pressed(GamepadButton::Start)) {
         next_state.set(GameState::MainMenu);
     }
     
@@ -1,4 +1,7 @@
 use crate::*;
+use crate::assists::AssistLevel;
+use crate::car_spec::{CarRoster, CarSpec, ROSTER};
+use crate::ghost::GhostSource;
 
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub enum GameState {
@@ -8,11 +11,24 @@ pub enum GameState {
     InGame,
 }
 
+/// What kind of session PLAY or TIME TRIAL starts - read when entering `GameState::InGame`
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GameMode {
+    #[default]
+    FreeDrive, // Open session with AI opponents
+    TimeTrial, // Alone against the clock and the personal best
+}
+
 #[derive(Resource)]
 pub struct GameSettings {
     pub motion_blur_enabled: bool,
     pub post_processing_enabled: bool,
     pub atmospheric_fog_enabled: bool,
+    pub abs: AssistLevel,
+    pub traction_control: AssistLevel,
+    pub stability_control: AssistLevel,
+    pub car: String, // Roster id of the car the player drives
+    pub ghost: GhostSource, // Recorded lap the ghost car replays
 }
 
 impl Default for GameSettings {
@@ -21,6 +37,11 @@ impl Default for GameSettings {
             motion_blur_enabled: true,
             post_processing_enabled: true, 
             atmospheric_fog_enabled: true, // Fog enabled by default for immersion
+            abs: AssistLevel::High, // Locked wheels can't steer - keep ABS on unless asked
+            traction_control: AssistLevel::Low,
+            stability_control: AssistLevel::Low,
+            car: ROSTER[0].to_string(),
+            ghost: GhostSource::PersonalBest,
         }
     }
 }
@@ -30,13 +51,15 @@ pub struct MenuPlugin;
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<GameState>()
+            .init_state::<GameMode>()
             .init_resource::<GameSettings>()
+            .init_resource::<MenuFocus>()
             .add_systems(OnEnter(GameState::MainMenu), setup_main_menu)
             .add_systems(OnExit(GameState::MainMenu), cleanup_main_menu)
-            .add_systems(Update, main_menu_system.run_if(in_state(GameState::MainMenu)))
+            .add_systems(Update, (gamepad_menu_system, main_menu_system).chain().run_if(in_state(GameState::MainMenu)))
             .add_systems(OnEnter(GameState::Settings), setup_settings_menu)
             .add_systems(OnExit(GameState::Settings), cleanup_settings_menu)
-            .add_systems(Update, settings_menu_system.run_if(in_state(GameState::Settings)));
+            .add_systems(Update, (gamepad_menu_system, (settings_menu_system, assist_settings_system, car_select_system, ghost_select_system)).chain().run_if(in_state(GameState::Settings)));
     }
 }
 
@@ -49,6 +72,9 @@ pub struct SettingsMenuUI;
 #[derive(Component)]
 pub struct PlayButton;
 
+#[derive(Component)]
+pub struct TimeTrialButton;
+
 #[derive(Component)]
 pub struct SettingsButton;
 
@@ -58,6 +84,25 @@ pub struct ExitButton;
 #[derive(Component)]
 pub struct BackButton;
 
+/// Sort key of a button in its menu's gamepad focus order - the pad walks the buttons from lowest to highest
+#[derive(Component)]
+pub struct MenuItem(pub usize);
+
+/// Gamepad focus within the current menu
+#[derive(Resource, Default)]
+pub struct MenuFocus {
+    pub index: usize, // Position among the menu's buttons sorted by their MenuItem
+    pub visible: bool, // Only outline the focused button once the pad has been used
+    pub stick_latched: bool, // Stick must return to centre before it moves focus again
+    pub pressed: Option<Entity>, // Button activated by the pad last frame, released next frame
+}
+
+// Number the buttons in the order they're spawned, so the pad focus runs top to bottom
+fn menu_item(order: &mut usize) -> (MenuItem, Outline) {
+    *order += 1;
+    (MenuItem(*order - 1), Outline::new(Val::Px(3.0), Val::ZERO, Color::NONE))
+}
+
 #[derive(Component)]
 pub struct MotionBlurToggle;
 
@@ -85,7 +130,75 @@ pub struct AtmosphericFogText;
 #[derive(Component)]
 pub struct AtmosphericFogButton;
 
-fn setup_main_menu(mut commands: Commands) {
+/// Driver aids that can be cycled from the settings menu
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssistSetting {
+    Abs,
+    TractionControl,
+    StabilityControl,
+}
+
+impl AssistSetting {
+    fn label(self) -> &'static str {
+        match self {
+            AssistSetting::Abs => "ABS",
+            AssistSetting::TractionControl => "TRACTION CONTROL",
+            AssistSetting::StabilityControl => "STABILITY CONTROL",
+        }
+    }
+
+    fn level(self, settings: &GameSettings) -> AssistLevel {
+        match self {
+            AssistSetting::Abs => settings.abs,
+            AssistSetting::TractionControl => settings.traction_control,
+            AssistSetting::StabilityControl => settings.stability_control,
+        }
+    }
+
+    fn level_mut(self, settings: &mut GameSettings) -> &mut AssistLevel {
+        match self {
+            AssistSetting::Abs => &mut settings.abs,
+            AssistSetting::TractionControl => &mut settings.traction_control,
+            AssistSetting::StabilityControl => &mut settings.stability_control,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct AssistButton(pub AssistSetting);
+
+#[derive(Component)]
+pub struct AssistText(pub AssistSetting);
+
+#[derive(Component)]
+pub struct CarSelectButton;
+
+#[derive(Component)]
+pub struct CarSelectText;
+
+#[derive(Component)]
+pub struct GhostSelectButton;
+
+#[derive(Component)]
+pub struct GhostSelectText;
+
+// Spec name once loaded, roster id until then
+fn car_label(roster: &CarRoster, specs: &Assets<CarSpec>, car_id: &str) -> String {
+    let name = roster.spec(car_id).and_then(|spec| specs.get(spec)).map_or(car_id, |spec| spec.name.as_str());
+    format!("CAR: {}", name.to_uppercase())
+}
+
+fn assist_button_color(level: AssistLevel) -> Color {
+    match level {
+        AssistLevel::Off => Color::srgb(0.8, 0.2, 0.2), // Red for OFF
+        AssistLevel::Low => Color::srgb(0.8, 0.6, 0.2), // Amber for LOW
+        AssistLevel::High => Color::srgb(0.2, 0.8, 0.2), // Green for HIGH
+    }
+}
+
+fn setup_main_menu(mut commands: Commands, mut focus: ResMut<MenuFocus>) {
+    *focus = MenuFocus::default();
+
     // Spawn a camera for the menu
     commands.spawn((
         Camera2d,
@@ -107,6 +220,8 @@ fn setup_main_menu(mut commands: Commands) {
             MainMenuUI,
         ))
         .with_children(|parent| {
+            let mut order = 0;
+
             // Title
             parent.spawn((
                 Text::new("BEVY VIBES"),
@@ -135,6 +250,7 @@ fn setup_main_menu(mut commands: Commands) {
                     },
                     BackgroundColor(Color::srgb(0.2, 0.5, 0.8)),
                     PlayButton,
+                    menu_item(&mut order),
                 ))
                 .with_children(|button| {
                     button.spawn((
@@ -147,6 +263,33 @@ fn setup_main_menu(mut commands: Commands) {
                     ));
                 });
 
+            // Time Trial Button
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        height: Val::Px(60.0),
+                        margin: UiRect::all(Val::Px(10.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.2, 0.6, 0.4)),
+                    TimeTrialButton,
+                    menu_item(&mut order),
+                ))
+                .with_children(|button| {
+                    button.spawn((
+                        Text::new("TIME TRIAL"),
+                        TextFont {
+                            font_size: 30.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                });
+
             // Settings Button
             parent
                 .spawn((
@@ -161,6 +304,7 @@ fn setup_main_menu(mut commands: Commands) {
                     },
                     BackgroundColor(Color::srgb(0.5, 0.5, 0.5)),
                     SettingsButton,
+                    menu_item(&mut order),
                 ))
                 .with_children(|button| {
                     button.spawn((
@@ -187,6 +331,7 @@ fn setup_main_menu(mut commands: Commands) {
                     },
                     BackgroundColor(Color::srgb(0.8, 0.2, 0.2)),
                     ExitButton,
+                    menu_item(&mut order),
                 ))
                 .with_children(|button| {
                     button.spawn((
@@ -201,18 +346,87 @@ fn setup_main_menu(mut commands: Commands) {
         });
 }
 
+// Move focus with the d-pad or left stick, activate with South, back out with East
+fn gamepad_menu_system(
+    gamepads: Query<&Gamepad>,
+    mut focus: ResMut<MenuFocus>,
+    mut item_query: Query<(Entity, &MenuItem, &mut Interaction, &mut Outline)>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // Release the button pressed last frame so it can fire again
+    if let Some(entity) = focus.pressed.take()
+        && let Ok((_, _, mut interaction, _)) = item_query.get_mut(entity)
+    {
+        *interaction = Interaction::None;
+    }
+
+    let mut items: Vec<(usize, Entity)> = item_query.iter().map(|(entity, item, _, _)| (item.0, entity)).collect();
+    if items.is_empty() {
+        return;
+    }
+    items.sort();
+
+    let mut step: i32 = 0;
+    let mut activate = false;
+    for gamepad in gamepads.iter() {
+        let stick_y = gamepad.left_stick().y;
+        if gamepad.just_pressed(GamepadButton::DPadUp) || (stick_y > 0.5 && !focus.stick_latched) {
+            step -= 1;
+        }
+        if gamepad.just_pressed(GamepadButton::DPadDown) || (stick_y < -0.5 && !focus.stick_latched) {
+            step += 1;
+        }
+        if stick_y.abs() > 0.5 {
+            focus.stick_latched = true;
+        } else if stick_y.abs() < 0.3 {
+            focus.stick_latched = false;
+        }
+
+        activate |= gamepad.just_pressed(GamepadButton::South);
+        if gamepad.just_pressed(GamepadButton::East) && *state.get() == GameState::Settings {
+            next_state.set(GameState::MainMenu);
+        }
+    }
+
+    if step != 0 || activate {
+        // The first touch only reveals the focus outline
+        if focus.visible {
+            focus.index = (focus.index as i32 + step).rem_euclid(items.len() as i32) as usize;
+        } else {
+            focus.visible = true;
+            activate = false;
+        }
+    }
+
+    let focused_entity = items.get(focus.index).map(|(_, entity)| *entity);
+    for (entity, _, mut interaction, mut outline) in item_query.iter_mut() {
+        let focused = focus.visible && Some(entity) == focused_entity;
+        outline.color = if focused { Color::WHITE } else { Color::NONE };
+
+        // Pressing the button here lets the menu systems handle pad and mouse clicks identically
+        if focused && activate {
+            *interaction = Interaction::Pressed;
+            focus.pressed = Some(entity);
+        }
+    }
+}
+
 fn cleanup_main_menu(mut commands: Commands, query: Query<Entity, With<MainMenuUI>>) {
     for entity in query.iter() {
         commands.entity(entity).despawn();
     }
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn main_menu_system(
     mut interaction_query: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>,
     play_button_query: Query<&Interaction, (Changed<Interaction>, With<PlayButton>)>,
+    time_trial_button_query: Query<&Interaction, (Changed<Interaction>, With<TimeTrialButton>)>,
     settings_button_query: Query<&Interaction, (Changed<Interaction>, With<SettingsButton>)>,
     exit_button_query: Query<&Interaction, (Changed<Interaction>, With<ExitButton>)>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut next_mode: ResMut<NextState<GameMode>>,
     mut exit: EventWriter<AppExit>,
 ) {
     // Handle button hover effects
@@ -232,6 +446,15 @@ fn main_menu_system(
     // Handle Play button
     for interaction in play_button_query.iter() {
         if *interaction == Interaction::Pressed {
+            next_mode.set(GameMode::FreeDrive);
+            next_state.set(GameState::InGame);
+        }
+    }
+
+    // Handle Time Trial button
+    for interaction in time_trial_button_query.iter() {
+        if *interaction == Interaction::Pressed {
+            next_mode.set(GameMode::TimeTrial);
             next_state.set(GameState::InGame);
         }
     }
@@ -251,7 +474,15 @@ fn main_menu_system(
     }
 }
 
-fn setup_settings_menu(mut commands: Commands, settings: Res<GameSettings>) {
+fn setup_settings_menu(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    roster: Res<CarRoster>,
+    specs: Res<Assets<CarSpec>>,
+    mut focus: ResMut<MenuFocus>,
+) {
+    *focus = MenuFocus::default();
+
     // Spawn a camera for the settings menu
     commands.spawn((
         Camera2d,
@@ -273,6 +504,8 @@ fn setup_settings_menu(mut commands: Commands, settings: Res<GameSettings>) {
             SettingsMenuUI,
         ))
         .with_children(|parent| {
+            let mut order = 0;
+
             // Title
             parent.spawn((
                 Text::new("SETTINGS"),
@@ -306,6 +539,7 @@ fn setup_settings_menu(mut commands: Commands, settings: Res<GameSettings>) {
                     }),
                     MotionBlurToggle,
                     MotionBlurButton,
+                    menu_item(&mut order),
                 ))
                 .with_children(|button| {
                     button.spawn((
@@ -341,6 +575,7 @@ fn setup_settings_menu(mut commands: Commands, settings: Res<GameSettings>) {
                     }),
                     PostProcessToggle,
                     PostProcessButton,
+                    menu_item(&mut order),
                 ))
                 .with_children(|button| {
                     button.spawn((
@@ -376,6 +611,7 @@ fn setup_settings_menu(mut commands: Commands, settings: Res<GameSettings>) {
                     }),
                     AtmosphericFogToggle,
                     AtmosphericFogButton,
+                    menu_item(&mut order),
                 ))
                 .with_children(|button| {
                     button.spawn((
@@ -392,6 +628,94 @@ fn setup_settings_menu(mut commands: Commands, settings: Res<GameSettings>) {
                     ));
                 });
 
+            // Driver assist levels - each button cycles OFF / LOW / HIGH
+            let assists = [AssistSetting::Abs, AssistSetting::TractionControl, AssistSetting::StabilityControl];
+            for assist in assists {
+                let level = assist.level(&settings);
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(300.0),
+                            height: Val::Px(60.0),
+                            margin: UiRect::all(Val::Px(10.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(assist_button_color(level)),
+                        AssistButton(assist),
+                        menu_item(&mut order),
+                    ))
+                    .with_children(|button| {
+                        button.spawn((
+                            Text::new(format!("{}: {}", assist.label(), level.label())),
+                            TextFont {
+                                font_size: 25.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                            AssistText(assist),
+                        ));
+                    });
+            }
+
+            // Car selection - cycles through the roster
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(300.0),
+                        height: Val::Px(60.0),
+                        margin: UiRect::all(Val::Px(10.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.5, 0.5, 0.5)),
+                    CarSelectButton,
+                    menu_item(&mut order),
+                ))
+                .with_children(|button| {
+                    button.spawn((
+                        Text::new(car_label(&roster, &specs, &settings.car)),
+                        TextFont {
+                            font_size: 25.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                        CarSelectText,
+                    ));
+                });
+
+            // Ghost car - cycles through the laps it can replay
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(300.0),
+                        height: Val::Px(60.0),
+                        margin: UiRect::all(Val::Px(10.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.5, 0.5, 0.5)),
+                    GhostSelectButton,
+                    menu_item(&mut order),
+                ))
+                .with_children(|button| {
+                    button.spawn((
+                        Text::new(format!("GHOST: {}", settings.ghost.label())),
+                        TextFont {
+                            font_size: 25.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                        GhostSelectText,
+                    ));
+                });
+
             // Back Button
             parent
                 .spawn((
@@ -406,6 +730,7 @@ fn setup_settings_menu(mut commands: Commands, settings: Res<GameSettings>) {
                     },
                     BackgroundColor(Color::srgb(0.5, 0.5, 0.5)),
                     BackButton,
+                    menu_item(&mut order),
                 ))
                 .with_children(|button| {
                     button.spawn((
@@ -426,8 +751,9 @@ fn cleanup_settings_menu(mut commands: Commands, query: Query<Entity, With<Setti
     }
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn settings_menu_system(
-    mut interaction_query: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>, Without<MotionBlurButton>, Without<PostProcessButton>, Without<AtmosphericFogButton>)>,
+    mut interaction_query: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>, Without<MotionBlurButton>, Without<PostProcessButton>, Without<AtmosphericFogButton>, Without<AssistButton>)>,
     motion_blur_query: Query<&Interaction, (Changed<Interaction>, With<MotionBlurToggle>)>,
     post_process_query: Query<&Interaction, (Changed<Interaction>, With<PostProcessToggle>)>,
     atmospheric_fog_query: Query<&Interaction, (Changed<Interaction>, With<AtmosphericFogToggle>)>,
@@ -533,4 +859,66 @@ fn settings_menu_system(
             next_state.set(GameState::MainMenu);
         }
     }
-} 
\ No newline at end of file
+}
+
+fn assist_settings_system(
+    mut button_query: Query<(&Interaction, &AssistButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut text_query: Query<(&mut Text, &AssistText)>,
+    mut settings: ResMut<GameSettings>,
+) {
+    for (interaction, button, mut color) in button_query.iter_mut() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        // Cycle the level and update the button to match
+        let level = button.0.level_mut(&mut settings);
+        *level = level.next();
+        let level = *level;
+        *color = BackgroundColor(assist_button_color(level));
+
+        for (mut text, assist_text) in text_query.iter_mut() {
+            if assist_text.0 == button.0 {
+                **text = format!("{}: {}", button.0.label(), level.label());
+            }
+        }
+    }
+}
+
+fn car_select_system(
+    button_query: Query<&Interaction, (Changed<Interaction>, With<CarSelectButton>)>,
+    mut text_query: Query<&mut Text, With<CarSelectText>>,
+    roster: Res<CarRoster>,
+    specs: Res<Assets<CarSpec>>,
+    mut settings: ResMut<GameSettings>,
+) {
+    for interaction in button_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        if let Some(next) = roster.next_id(&settings.car) {
+            settings.car = next.to_string();
+        }
+        for mut text in text_query.iter_mut() {
+            **text = car_label(&roster, &specs, &settings.car);
+        }
+    }
+}
+
+fn ghost_select_system(
+    button_query: Query<&Interaction, (Changed<Interaction>, With<GhostSelectButton>)>,
+    mut text_query: Query<&mut Text, With<GhostSelectText>>,
+    mut settings: ResMut<GameSettings>,
+) {
+    for interaction in button_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        settings.ghost = settings.ghost.next();
+        for mut text in text_query.iter_mut() {
+            **text = format!("GHOST: {}", settings.ghost.label());
+        }
+    }
+}
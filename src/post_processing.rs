@@ -1,6 +1,9 @@
+// ShaderType derive emits unused size-check helpers at module scope, out of reach of an attribute on the struct
+#![allow(dead_code)]
+
 use crate::*;
 use crate::menu::GameState;
-use crate::car::Car;
+use crate::car::{Car, CameraTarget};
 
 use bevy::{
     core_pipeline::{
@@ -216,7 +219,7 @@ fn setup_post_processing(
 }
 
 fn update_post_process_settings(
-    car_query: Query<&Car>,
+    car_query: Query<&Car, With<CameraTarget>>,
     mut camera_query: Query<&mut RacingPostProcessSettings>,
     settings: Res<crate::menu::GameSettings>,
 ) {
@@ -225,16 +228,17 @@ fn update_post_process_settings(
             if settings.post_processing_enabled {
                 // Calculate speed factor (0.0 to 1.0)
                 let speed_factor = (car.speed.abs() / car.max_speed).clamp(0.0, 1.0);
+                let boost_factor = if car.boosting { 1.0 } else { 0.0 }; // Nitro pushes every effect further
                 
                 // Speed-based effects - increased intensity
-                post_settings.speed_intensity = speed_factor;
-                post_settings.chromatic_aberration = 0.004 + speed_factor * 0.012;
-                post_settings.speed_lines = speed_factor * 0.7;
-                post_settings.vignette_strength = 0.4 + speed_factor * 0.5;
+                post_settings.speed_intensity = speed_factor + boost_factor * 0.5;
+                post_settings.chromatic_aberration = 0.004 + speed_factor * 0.012 + boost_factor * 0.008;
+                post_settings.speed_lines = speed_factor * 0.7 + boost_factor * 0.3;
+                post_settings.vignette_strength = 0.4 + speed_factor * 0.5 + boost_factor * 0.2;
                 
                 // Enhanced visuals for racing - more dramatic
-                post_settings.color_saturation = 1.3 + speed_factor * 0.4;
-                post_settings.contrast = 1.2 + speed_factor * 0.3;
+                post_settings.color_saturation = 1.3 + speed_factor * 0.4 + boost_factor * 0.2;
+                post_settings.contrast = 1.2 + speed_factor * 0.3 + boost_factor * 0.1;
             } else {
                 // Disable all effects when post-processing is off
                 post_settings.speed_intensity = 0.0;
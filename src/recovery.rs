@@ -0,0 +1,197 @@
+use crate::*;
+use crate::menu::GameState;
+use crate::car::{Car, WheelSuspension};
+use crate::input::CarInput;
+use crate::simulation::CarSimulationSet;
+use crate::surface::SurfaceType;
+use bevy_rapier3d::prelude::*;
+
+pub struct RecoveryPlugin;
+
+impl Plugin for RecoveryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CarReset>()
+            .add_systems(FixedUpdate, (
+                car_recovery_system,
+                recovery_ghost_period_system,
+            ).chain().in_set(CarSimulationSet).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Watches a car for being flipped or stuck and puts it back on the ground
+#[derive(Component)]
+pub struct CarRecovery {
+    pub upside_down_delay: f32, // Seconds on its roof or side before resetting automatically
+    pub stuck_delay: f32, // Seconds of pedal without moving before resetting automatically
+    pub ghost_duration: f32, // Seconds the car passes through everything after a reset
+    pub ride_height: f32, // Height above the ground the car is dropped at
+    pub checkpoint: Option<Transform>, // Where a manual reset sends the car, set by the race
+    pub last_safe: Option<Transform>, // Last place the car sat level on all its wheels
+    pub upside_down_timer: f32,
+    pub stuck_timer: f32,
+    pub ghost_timer: f32, // Remaining no-collision time, zero when solid
+}
+
+impl Default for CarRecovery {
+    fn default() -> Self {
+        Self {
+            upside_down_delay: 2.0,
+            stuck_delay: 4.0, // Long enough to wriggle free of a wall first
+            ghost_duration: 2.0,
+            ride_height: 0.6, // Matches the spawn height above the ground so the suspension settles gently
+            checkpoint: None,
+            last_safe: None,
+            upside_down_timer: 0.0,
+            stuck_timer: 0.0,
+            ghost_timer: 0.0,
+        }
+    }
+}
+
+/// Why a car was put back on the ground
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResetReason {
+    Manual,
+    Flipped,
+    Stuck,
+}
+
+/// Sent whenever a car is reset, for HUD messages and race penalties
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CarReset {
+    pub car: Entity,
+    pub reason: ResetReason,
+}
+
+/// Tipped far enough that the car can't drive away - roof or side down
+pub fn is_upside_down(up: Vec3) -> bool {
+    up.y < 0.3
+}
+
+/// Same heading, wheels down
+pub fn upright_rotation(rotation: Quat) -> Quat {
+    let forward = rotation * Vec3::NEG_Z;
+    let heading = Vec3::new(forward.x, 0.0, forward.z);
+    if heading.length_squared() < 0.01 {
+        // Pointing straight up or down - use where the roof faces instead
+        let up = rotation * Vec3::Y;
+        return Transform::default().looking_to(Vec3::new(up.x, 0.0, up.z).normalize_or(Vec3::NEG_Z), Vec3::Y).rotation;
+    }
+    Transform::default().looking_to(heading.normalize(), Vec3::Y).rotation
+}
+
+fn car_recovery_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    rapier_context: ReadRapierContext,
+    ground_query: Query<(), With<SurfaceType>>,
+    mut car_query: Query<(Entity, &mut Transform, &mut Velocity, &mut Car, &CarInput, &mut CarRecovery)>,
+    mut wheel_query: Query<&mut WheelSuspension>,
+    mut reset_events: EventWriter<CarReset>,
+) {
+    let dt = time.delta_secs();
+    let Ok(rapier_context) = rapier_context.single() else {
+        return;
+    };
+
+    for (entity, mut transform, mut velocity, mut car, input, mut recovery) in car_query.iter_mut() {
+        let up = *transform.up();
+        let speed = velocity.linvel.length();
+
+        // Resting on its roof or side
+        if is_upside_down(up) && speed < 3.0 {
+            recovery.upside_down_timer += dt;
+        } else {
+            recovery.upside_down_timer = 0.0;
+        }
+
+        // Pedal to the floor but going nowhere - beached on an obstacle or wedged against a wall
+        let pedal = input.throttle.max(input.brake);
+        if pedal > 0.5 && speed < 0.5 && !is_upside_down(up) {
+            recovery.stuck_timer += dt;
+        } else {
+            recovery.stuck_timer = 0.0;
+        }
+
+        if car.grounded_wheels >= 4 && up.y > 0.9 {
+            recovery.last_safe = Some(Transform::from_translation(transform.translation).with_rotation(upright_rotation(transform.rotation)));
+        }
+
+        let reason = if input.reset {
+            ResetReason::Manual
+        } else if recovery.upside_down_timer >= recovery.upside_down_delay {
+            ResetReason::Flipped
+        } else if recovery.stuck_timer >= recovery.stuck_delay {
+            ResetReason::Stuck
+        } else {
+            continue;
+        };
+
+        // Drop onto real track surface under the car - props and buildings don't count
+        let is_ground = |hit| ground_query.contains(hit);
+        let filter = QueryFilter::default()
+            .exclude_rigid_body(entity)
+            .exclude_sensors()
+            .predicate(&is_ground);
+        let origin = Vec3::new(transform.translation.x, transform.translation.y + 20.0, transform.translation.z);
+        let ground_below = rapier_context
+            .cast_ray(origin, Vec3::NEG_Y, 100.0, true, filter)
+            .map(|(_, distance)| {
+                let ground = origin + Vec3::NEG_Y * distance;
+                Transform::from_translation(ground + Vec3::Y * recovery.ride_height)
+                    .with_rotation(upright_rotation(transform.rotation))
+            });
+
+        // A manual reset goes back to the race's last checkpoint, automatic ones stay where the car is
+        let target = match reason {
+            ResetReason::Manual => recovery.checkpoint.or(ground_below).or(recovery.last_safe),
+            ResetReason::Flipped | ResetReason::Stuck => ground_below.or(recovery.last_safe).or(recovery.checkpoint),
+        };
+        let Some(target) = target else {
+            continue;
+        };
+
+        *transform = target;
+        *velocity = Velocity::zero();
+        car.speed = 0.0;
+        car.wheel_speed = 0.0;
+        car.gear = 1;
+        car.reversing = false;
+        car.shift_timer = 0.0;
+        for mut wheel in wheel_query.iter_mut().filter(|wheel| wheel.car == entity) {
+            wheel.angular_velocity = 0.0;
+        }
+
+        recovery.upside_down_timer = 0.0;
+        recovery.stuck_timer = 0.0;
+        recovery.ghost_timer = recovery.ghost_duration;
+        // Push against nothing while ghosted - the suspension rays still hold the car up, and sensors like
+        // checkpoint gates still see it go through
+        commands.entity(entity).insert(SolverGroups::new(Group::NONE, Group::NONE));
+
+        reset_events.write(CarReset { car: entity, reason });
+        println!("Car reset: {:?}", reason);
+    }
+}
+
+// Blink ghosted cars and make them solid again once the ghost period is over
+fn recovery_ghost_period_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut car_query: Query<(Entity, &mut CarRecovery, &mut Visibility)>,
+) {
+    for (entity, mut recovery, mut visibility) in car_query.iter_mut() {
+        if recovery.ghost_timer <= 0.0 {
+            continue;
+        }
+
+        recovery.ghost_timer = (recovery.ghost_timer - time.delta_secs()).max(0.0);
+        if recovery.ghost_timer <= 0.0 {
+            commands.entity(entity).insert(SolverGroups::default()); // Rapier ignores removal, so restore explicitly
+            *visibility = Visibility::Inherited;
+        } else {
+            let blink_on = (recovery.ghost_timer * 10.0) as i32 % 2 == 0;
+            *visibility = if blink_on { Visibility::Inherited } else { Visibility::Hidden };
+        }
+    }
+}
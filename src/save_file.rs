@@ -0,0 +1,33 @@
+use ron::ser::PrettyConfig;
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SaveFileError {
+    #[error("could not access save file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse save file: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("could not encode save file: {0}")]
+    Encode(#[from] ron::Error),
+}
+
+/// Read a RON save file. None when there's no file - nothing has been saved there yet
+pub fn load_ron<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, SaveFileError> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(ron::from_str(&contents)?)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Write a RON save file, creating its directory if needed
+pub fn save_ron<T: Serialize>(value: &T, path: &Path, config: PrettyConfig) -> Result<(), SaveFileError> {
+    let contents = ron::ser::to_string_pretty(value, config)?;
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    std::fs::write(path, contents)?;
+    Ok(())
+}
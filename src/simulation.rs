@@ -0,0 +1,113 @@
+use crate::*;
+use crate::input::CarInputSet;
+use bevy::app::RunFixedMainLoopSystem;
+use bevy_rapier3d::prelude::*;
+
+/// Runs the car simulation and rapier on a fixed tick so handling doesn't depend on frame rate,
+/// and smooths physics bodies between ticks for rendering
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        let config = app.world_mut().get_resource_or_init::<SimulationConfig>().clone();
+        app.insert_resource(Time::<Fixed>::from_hz(config.tick_rate))
+            .insert_resource(config.timestep_mode())
+            .add_plugins(RapierPhysicsPlugin::<NoUserData>::default().in_fixed_schedule())
+            .configure_sets(FixedUpdate, (
+                CarInputSet,
+                CarSimulationSet,
+                PhysicsSet::SyncBackend,
+            ).chain())
+            .add_systems(First, apply_tick_rate.run_if(resource_changed::<SimulationConfig>))
+            .add_systems(FixedUpdate, (
+                record_previous_pose.after(CarSimulationSet).before(PhysicsSet::SyncBackend),
+                record_current_pose.after(PhysicsSet::Writeback),
+            ))
+            .add_systems(RunFixedMainLoop, (
+                restore_physics_pose.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+                interpolate_physics_pose.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+            ));
+    }
+}
+
+/// Fixed-tick systems that drive cars run in this set, after input is sampled and before rapier steps
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CarSimulationSet;
+
+/// Simulation tick rate - insert before adding `SimulationPlugin`, or change it at runtime
+#[derive(Resource, Clone)]
+pub struct SimulationConfig {
+    pub tick_rate: f64, // Fixed ticks per second, independent of the render frame rate
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            tick_rate: 60.0,
+        }
+    }
+}
+
+impl SimulationConfig {
+    /// Rapier advances by exactly one tick each time the fixed schedule runs
+    pub fn timestep_mode(&self) -> TimestepMode {
+        TimestepMode::Fixed {
+            dt: (1.0 / self.tick_rate) as f32,
+            substeps: 1,
+        }
+    }
+}
+
+/// Rigid body pose at either end of the last fixed tick. The body's `Transform` is drawn part way
+/// between them and put back to the latest pose before the next tick, so rapier never sees the blend
+#[derive(Component, Default)]
+pub struct PhysicsInterpolation {
+    pub previous: Option<Transform>, // Going into the tick, after any teleport this tick
+    pub current: Option<Transform>, // Where the tick left the body
+}
+
+fn apply_tick_rate(
+    config: Res<SimulationConfig>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut timestep_mode: ResMut<TimestepMode>,
+) {
+    fixed_time.set_timestep_hz(config.tick_rate);
+    *timestep_mode = config.timestep_mode();
+}
+
+fn record_previous_pose(mut body_query: Query<(&Transform, &mut PhysicsInterpolation)>) {
+    for (transform, mut interpolation) in body_query.iter_mut() {
+        interpolation.previous = Some(*transform);
+    }
+}
+
+fn record_current_pose(mut body_query: Query<(&Transform, &mut PhysicsInterpolation)>) {
+    for (transform, mut interpolation) in body_query.iter_mut() {
+        interpolation.current = Some(*transform);
+    }
+}
+
+fn restore_physics_pose(mut body_query: Query<(&mut Transform, &PhysicsInterpolation)>) {
+    for (mut transform, interpolation) in body_query.iter_mut() {
+        if let Some(current) = interpolation.current {
+            transform.translation = current.translation;
+            transform.rotation = current.rotation;
+        }
+    }
+}
+
+fn interpolate_physics_pose(
+    fixed_time: Res<Time<Fixed>>,
+    mut body_query: Query<(&mut Transform, &PhysicsInterpolation)>,
+) {
+    // How far the clock has run into the next tick
+    let alpha = fixed_time.overstep_fraction();
+
+    for (mut transform, interpolation) in body_query.iter_mut() {
+        let (Some(previous), Some(current)) = (interpolation.previous, interpolation.current) else {
+            continue;
+        };
+        transform.translation = previous.translation.lerp(current.translation, alpha);
+        transform.rotation = previous.rotation.slerp(current.rotation, alpha);
+    }
+}
@@ -0,0 +1,413 @@
+use crate::*;
+use crate::menu::GameState;
+use crate::car::{Car, WheelSuspension};
+use crate::world::GameEntity;
+use bevy::audio::{AddAudioSource, Decodable, SpatialScale, Source, Volume};
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub struct SurfacePlugin;
+
+impl Plugin for SurfacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<TireSoundClip>()
+            .add_systems(Startup, (setup_tire_particle_assets, setup_tire_sound_assets))
+            .add_systems(Update, (
+                spawn_tire_particles,
+                update_tire_particles,
+                tire_audio_system,
+            ).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Ground material under a collider - wheels read it from whatever their suspension ray hits.
+/// Colliders without one drive like asphalt.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SurfaceType {
+    #[default]
+    Asphalt,
+    Grass,
+    Gravel,
+    Ice,
+}
+
+/// What a tyre kicks up when it slides or rolls over a surface
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticleKind {
+    Smoke, // Only when the tyre is sliding
+    Grass,
+    Dust,
+    Spray,
+}
+
+/// Which tyre sound loop plays on a surface
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TireSound {
+    Squeal,
+    Rustle,
+    Crunch,
+    Hiss,
+}
+
+/// How a surface feels to drive on
+#[derive(Clone, Copy, Debug)]
+pub struct SurfaceProperties {
+    pub grip: f32, // Friction coefficient fed to the tyre model, asphalt is 1.0
+    pub rolling_resistance: f32, // Multiplier on the car's rolling resistance - loose surfaces bog the car down
+    pub loose: bool, // Throws up particles whenever the car rolls over it, not just when sliding
+    pub particle: ParticleKind,
+    pub particle_color: Color,
+    pub tire_sound: TireSound,
+}
+
+impl SurfaceType {
+    pub fn properties(self) -> SurfaceProperties {
+        match self {
+            SurfaceType::Asphalt => SurfaceProperties {
+                grip: 1.0,
+                rolling_resistance: 1.0,
+                loose: false,
+                particle: ParticleKind::Smoke,
+                particle_color: Color::srgba(0.85, 0.85, 0.85, 0.6),
+                tire_sound: TireSound::Squeal,
+            },
+            SurfaceType::Grass => SurfaceProperties {
+                grip: 0.6,
+                rolling_resistance: 6.0,
+                loose: true,
+                particle: ParticleKind::Grass,
+                particle_color: Color::srgb(0.25, 0.5, 0.15),
+                tire_sound: TireSound::Rustle,
+            },
+            SurfaceType::Gravel => SurfaceProperties {
+                grip: 0.55,
+                rolling_resistance: 20.0, // Gravel traps are meant to stop cars
+                loose: true,
+                particle: ParticleKind::Dust,
+                particle_color: Color::srgba(0.75, 0.65, 0.5, 0.8),
+                tire_sound: TireSound::Crunch,
+            },
+            SurfaceType::Ice => SurfaceProperties {
+                grip: 0.15,
+                rolling_resistance: 0.5,
+                loose: false,
+                particle: ParticleKind::Spray,
+                particle_color: Color::srgba(0.9, 0.95, 1.0, 0.7),
+                tire_sound: TireSound::Hiss,
+            },
+        }
+    }
+}
+
+/// How hard a tyre is sliding - 1.0 is where it starts to smoke and squeal
+fn slide_amount(wheel: &WheelSuspension) -> f32 {
+    (wheel.slip_ratio.abs() / 0.3).max(wheel.slip_angle.abs() / 0.2)
+}
+
+#[derive(Resource)]
+struct TireParticleAssets {
+    mesh: Handle<Mesh>,
+    materials: Vec<(SurfaceType, Handle<StandardMaterial>)>,
+    spawn_interval: f32, // Seconds between puffs from each tyre - with the lifetime this caps the particles per wheel
+    since_spawn: f32,
+}
+
+#[derive(Component)]
+pub struct TireParticle {
+    pub velocity: Vec3,
+    pub age: f32,
+    pub lifetime: f32,
+}
+
+fn setup_tire_particle_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // One shared mesh and a material per surface keeps particle spawning cheap
+    let materials = [SurfaceType::Asphalt, SurfaceType::Grass, SurfaceType::Gravel, SurfaceType::Ice]
+        .into_iter()
+        .map(|surface| {
+            let material = materials.add(StandardMaterial {
+                base_color: surface.properties().particle_color,
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            });
+            (surface, material)
+        })
+        .collect();
+
+    commands.insert_resource(TireParticleAssets {
+        mesh: meshes.add(Sphere::new(0.08)),
+        materials,
+        spawn_interval: 1.0 / 30.0,
+        since_spawn: 0.0,
+    });
+}
+
+fn spawn_tire_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particle_assets: ResMut<TireParticleAssets>,
+    car_query: Query<&Velocity>,
+    wheel_query: Query<&WheelSuspension>,
+) {
+    // Spawn at a fixed rate rather than every frame, so high frame rates don't flood the scene
+    particle_assets.since_spawn += time.delta_secs();
+    if particle_assets.since_spawn < particle_assets.spawn_interval {
+        return;
+    }
+    particle_assets.since_spawn = 0.0;
+
+    for wheel in wheel_query.iter().filter(|wheel| wheel.grounded) {
+        let Ok(velocity) = car_query.get(wheel.car) else {
+            continue;
+        };
+        let properties = wheel.surface.properties();
+        let speed = velocity.linvel.length();
+
+        // Sliding tyres smoke everywhere, loose surfaces throw debris whenever the car is moving
+        let sliding = slide_amount(wheel) > 1.0;
+        let kicking_up = properties.loose && speed > 3.0;
+        if !sliding && !kicking_up {
+            continue;
+        }
+
+        let Some((_, material)) = particle_assets.materials.iter().find(|(surface, _)| *surface == wheel.surface) else {
+            continue;
+        };
+
+        // Debris trails the car and pops up, smoke mostly hangs in the air
+        let (trail, lift) = match properties.particle {
+            ParticleKind::Smoke => (0.1, 0.5),
+            ParticleKind::Grass | ParticleKind::Dust => (0.4, 2.0),
+            ParticleKind::Spray => (0.3, 1.0),
+        };
+        commands.spawn((
+            Mesh3d(particle_assets.mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(wheel.contact_point + Vec3::Y * 0.05),
+            TireParticle {
+                velocity: -velocity.linvel * trail + Vec3::Y * lift,
+                age: 0.0,
+                lifetime: 0.6,
+            },
+            GameEntity, // Mark for cleanup
+        ));
+    }
+}
+
+fn update_tire_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particle_query: Query<(Entity, &mut Transform, &mut TireParticle)>,
+) {
+    let dt = time.delta_secs();
+
+    for (entity, mut transform, mut particle) in particle_query.iter_mut() {
+        particle.age += dt;
+        if particle.age >= particle.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // Drift, slow down and shrink away
+        let velocity = particle.velocity;
+        transform.translation += velocity * dt;
+        particle.velocity *= 1.0 - 3.0 * dt;
+        let remaining = 1.0 - particle.age / particle.lifetime;
+        transform.scale = Vec3::splat(0.5 + remaining * 1.5);
+    }
+}
+
+const TIRE_SOUND_SAMPLE_RATE: u32 = 44100;
+const TIRE_SOUND_AUDIBLE: f32 = 0.01; // Quieter than this a loop isn't started, or swapped for another surface
+
+/// Looping tyre noise for one surface, synthesised on the fly so every surface has a sound without shipping clips
+#[derive(Asset, TypePath, Clone, Copy)]
+pub struct TireSoundClip {
+    pub sound: TireSound,
+}
+
+impl Decodable for TireSoundClip {
+    type DecoderItem = f32;
+    type Decoder = TireNoise;
+
+    fn decoder(&self) -> Self::Decoder {
+        TireNoise { sound: self.sound, seed: 0x2545_f491, phase: 0.0, time: 0.0, low: 0.0, crackle: 0.0 }
+    }
+}
+
+/// Endless mono sample stream behind a `TireSoundClip`
+pub struct TireNoise {
+    sound: TireSound,
+    seed: u32, // xorshift state for the noise
+    phase: f32, // Squeal tone phase (radians)
+    time: f32, // Seconds since the loop started
+    low: f32, // Low-passed noise
+    crackle: f32, // Envelope of the current gravel crackle
+}
+
+impl TireNoise {
+    fn noise(&mut self) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        self.seed as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+impl Iterator for TireNoise {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let dt = 1.0 / TIRE_SOUND_SAMPLE_RATE as f32;
+        self.time += dt;
+        let noise = self.noise();
+
+        let sample = match self.sound {
+            // A wavering tone with a little grit on top
+            TireSound::Squeal => {
+                let wobble = 1.0 + (self.time * 7.0 * 2.0 * PI).sin() * 0.03;
+                self.phase = (self.phase + 1100.0 * wobble * 2.0 * PI * dt) % (2.0 * PI);
+                self.phase.sin() * 0.5 + noise * 0.1
+            }
+            // Soft low noise that flutters as the blades brush past
+            TireSound::Rustle => {
+                self.low += (noise - self.low) * 0.08;
+                let flutter = 0.6 + 0.4 * (self.time * 13.0 * 2.0 * PI).sin();
+                self.low * flutter * 2.5
+            }
+            // Low rumble broken up by stones popping under the tread
+            TireSound::Crunch => {
+                if self.noise() > 0.996 {
+                    self.crackle = 1.0;
+                }
+                self.crackle *= 0.995;
+                self.low += (noise - self.low) * 0.3;
+                self.low * (0.25 + self.crackle * 1.2)
+            }
+            // Thin high noise - the low-passed part taken out
+            TireSound::Hiss => {
+                self.low += (noise - self.low) * 0.05;
+                (noise - self.low) * 0.4
+            }
+        };
+        Some(sample.clamp(-1.0, 1.0))
+    }
+}
+
+impl Source for TireNoise {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        TIRE_SOUND_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[derive(Resource)]
+struct TireSoundAssets {
+    clips: Vec<(TireSound, Handle<TireSoundClip>)>,
+}
+
+/// Tyre audio emitter riding on a car - plays the loop for the surface its tyres are working hardest
+#[derive(Component)]
+pub struct TireAudio {
+    pub car: Entity,
+    pub sound: TireSound,
+}
+
+fn setup_tire_sound_assets(mut commands: Commands, mut clips: ResMut<Assets<TireSoundClip>>) {
+    let clips = [TireSound::Squeal, TireSound::Rustle, TireSound::Crunch, TireSound::Hiss]
+        .into_iter()
+        .map(|sound| (sound, clips.add(TireSoundClip { sound })))
+        .collect();
+    commands.insert_resource(TireSoundAssets { clips });
+}
+
+// Pick each car's tyre loop from the ground under its wheels, and set volume from slip and pitch from speed
+fn tire_audio_system(
+    mut commands: Commands,
+    sound_assets: Res<TireSoundAssets>,
+    car_query: Query<(Entity, &Velocity), With<Car>>,
+    wheel_query: Query<&WheelSuspension>,
+    mut emitter_query: Query<(Entity, &TireAudio, Option<&mut SpatialAudioSink>)>,
+) {
+    // Loudest grounded wheel on each car: (surface sound, volume, slide)
+    let mut loudest: HashMap<Entity, (TireSound, f32, f32)> = HashMap::new();
+    for wheel in wheel_query.iter().filter(|wheel| wheel.grounded) {
+        let Ok((_, velocity)) = car_query.get(wheel.car) else {
+            continue;
+        };
+        let properties = wheel.surface.properties();
+        let speed = velocity.linvel.length();
+        let slide = slide_amount(wheel);
+
+        // Loose ground is heard whenever the car rolls over it, any tyre once it starts to let go
+        let rolling = if properties.loose { (speed / 25.0).min(1.0) * 0.6 } else { 0.0 };
+        let sliding = ((slide - 0.7) / 1.3).clamp(0.0, 1.0) * (speed / 5.0).min(1.0);
+        let volume = (rolling + sliding).min(1.0);
+        let entry = loudest.entry(wheel.car).or_insert((properties.tire_sound, 0.0, 0.0));
+        if volume > entry.1 {
+            *entry = (properties.tire_sound, volume, slide);
+        }
+    }
+
+    let mut emitters: HashMap<Entity, Entity> = HashMap::new();
+    for (entity, audio, sink) in emitter_query.iter_mut() {
+        let Ok((_, velocity)) = car_query.get(audio.car) else {
+            continue;
+        };
+        let (sound, volume, slide) = loudest.get(&audio.car).copied().unwrap_or((audio.sound, 0.0, 0.0));
+
+        // Switching surface swaps the loop, otherwise the running one follows the car
+        if sound != audio.sound && volume > TIRE_SOUND_AUDIBLE {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        emitters.insert(audio.car, entity);
+        if let Some(mut sink) = sink {
+            sink.set_volume(Volume::Linear(volume));
+            sink.set_speed(tire_sound_pitch(velocity.linvel.length(), slide));
+        }
+    }
+
+    for (car, velocity) in car_query.iter() {
+        let Some(&(sound, volume, slide)) = loudest.get(&car) else {
+            continue;
+        };
+        if emitters.contains_key(&car) || volume <= TIRE_SOUND_AUDIBLE {
+            continue;
+        }
+        let Some((_, clip)) = sound_assets.clips.iter().find(|(clip_sound, _)| *clip_sound == sound) else {
+            continue;
+        };
+        commands.spawn((
+            AudioPlayer(clip.clone()),
+            PlaybackSettings::LOOP
+                .with_volume(Volume::Linear(volume))
+                .with_speed(tire_sound_pitch(velocity.linvel.length(), slide))
+                .with_spatial(true)
+                .with_spatial_scale(SpatialScale::new(0.1)), // Heard clearly from the chase camera, fades for distant cars
+            TireAudio { car, sound },
+            Transform::default(),
+            ChildOf(car),
+        ));
+    }
+}
+
+/// Playback speed for a tyre loop - higher as the car goes faster and the tyre slides harder
+fn tire_sound_pitch(speed: f32, slide: f32) -> f32 {
+    0.8 + (speed / 40.0).min(1.0) * 0.3 + slide.min(2.0) * 0.1
+}
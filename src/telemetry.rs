@@ -0,0 +1,244 @@
+use crate::*;
+use crate::menu::GameState;
+use crate::car::{Car, AppliedForces};
+use crate::input::CarInput;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+pub struct TelemetryPlugin;
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TelemetryConfig>()
+            .init_resource::<Telemetry>()
+            .add_systems(OnEnter(GameState::InGame), start_session)
+            .add_systems(OnExit(GameState::InGame), export_session)
+            .add_systems(FixedPostUpdate, record_telemetry_system.run_if(in_state(GameState::InGame)))
+            .add_systems(Update, export_hotkey_system
+                .run_if(resource_exists::<ButtonInput<KeyCode>>)
+                .run_if(in_state(GameState::InGame)))
+            .add_systems(Last, export_session.run_if(on_event::<AppExit>).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// What gets recorded and where exports go
+#[derive(Resource, Clone)]
+pub struct TelemetryConfig {
+    pub enabled: bool,
+    pub capacity: usize, // Samples kept - the oldest are dropped once it fills
+    pub format: TelemetryFormat,
+    pub export_key: KeyCode,
+    pub export_on_session_end: bool, // Write the buffer out when leaving the game or closing the window
+    pub directory: PathBuf, // Relative to the working directory
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            capacity: 36_000, // Ten minutes of one car at 60 ticks per second
+            format: TelemetryFormat::Csv,
+            export_key: KeyCode::F8,
+            export_on_session_end: false,
+            directory: PathBuf::from("telemetry"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TelemetryFormat {
+    Csv, // One flat row per sample, for spreadsheets and plotting
+    Json, // An array of samples with the forces nested
+}
+
+impl TelemetryFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            TelemetryFormat::Csv => "csv",
+            TelemetryFormat::Json => "json",
+        }
+    }
+}
+
+/// One car's state at the end of one simulation tick
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct TelemetrySample {
+    pub tick: u64, // Ticks since the session started
+    pub time: f64, // Simulated seconds since the session started
+    pub car: u32, // Entity index - tells cars apart when several are recorded
+    pub position: [f32; 3],
+    pub velocity: [f32; 3],
+    pub yaw_rate: f32, // rad/s, positive turning left
+    pub speed: f32, // Along the car's heading, negative reversing
+    pub throttle: f32,
+    pub brake: f32,
+    pub steer: f32,
+    pub handbrake: bool,
+    pub boost: bool,
+    pub steer_angle: f32,
+    pub gear: usize,
+    pub reversing: bool,
+    pub rpm: f32,
+    pub drift_angle: f32,
+    pub grounded_wheels: usize,
+    pub forces: AppliedForces,
+}
+
+#[derive(Debug, Error)]
+pub enum TelemetryError {
+    #[error("could not write telemetry: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not encode telemetry: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Ring buffer of samples for every car, one per tick. Read it directly from headless runs, or export it
+#[derive(Resource, Default)]
+pub struct Telemetry {
+    samples: VecDeque<TelemetrySample>,
+    tick: u64,
+    time: f64,
+}
+
+impl Telemetry {
+    pub fn samples(&self) -> impl Iterator<Item = &TelemetrySample> {
+        self.samples.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.tick = 0;
+        self.time = 0.0;
+    }
+
+    /// Add a sample, dropping the oldest once `capacity` are held
+    pub fn push(&mut self, sample: TelemetrySample, capacity: usize) {
+        while self.samples.len() >= capacity.max(1) {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "tick,time,car,x,y,z,vx,vy,vz,yaw_rate,speed,throttle,brake,steer,handbrake,boost,steer_angle,gear,reversing,rpm,drift_angle,grounded_wheels,\
+             suspension,drive,brake_force,tire_longitudinal,tire_lateral,rolling_resistance,boost_force,drag,front_lift,rear_lift,stability_brake,angular_damping,yaw_damping\n",
+        );
+        for sample in &self.samples {
+            let [x, y, z] = sample.position;
+            let [vx, vy, vz] = sample.velocity;
+            let forces = &sample.forces;
+            let _ = writeln!(
+                csv,
+                "{},{:.4},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                sample.tick, sample.time, sample.car, x, y, z, vx, vy, vz, sample.yaw_rate, sample.speed,
+                sample.throttle, sample.brake, sample.steer, sample.handbrake, sample.boost, sample.steer_angle,
+                sample.gear, sample.reversing, sample.rpm, sample.drift_angle, sample.grounded_wheels,
+                forces.suspension, forces.drive, forces.brake, forces.tire_longitudinal, forces.tire_lateral,
+                forces.rolling_resistance, forces.boost, forces.drag, forces.front_lift, forces.rear_lift,
+                forces.stability_brake, forces.angular_damping, forces.yaw_damping,
+            );
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> Result<String, TelemetryError> {
+        Ok(serde_json::to_string_pretty(&self.samples)?)
+    }
+
+    /// Write every buffered sample to `path`, creating its folder if needed
+    pub fn export(&self, path: &Path, format: TelemetryFormat) -> Result<(), TelemetryError> {
+        let contents = match format {
+            TelemetryFormat::Csv => self.to_csv(),
+            TelemetryFormat::Json => self.to_json()?,
+        };
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+fn start_session(mut telemetry: ResMut<Telemetry>) {
+    telemetry.clear();
+}
+
+fn record_telemetry_system(
+    time: Res<Time>,
+    config: Res<TelemetryConfig>,
+    mut telemetry: ResMut<Telemetry>,
+    car_query: Query<(Entity, &Transform, &Velocity, &Car, &CarInput)>,
+) {
+    if !config.enabled {
+        return;
+    }
+    telemetry.time += time.delta_secs_f64();
+
+    for (entity, transform, velocity, car, input) in car_query.iter() {
+        let sample = TelemetrySample {
+            tick: telemetry.tick,
+            time: telemetry.time,
+            car: entity.index(),
+            position: transform.translation.to_array(),
+            velocity: velocity.linvel.to_array(),
+            yaw_rate: velocity.angvel.dot(*transform.up()),
+            speed: car.speed,
+            throttle: input.throttle,
+            brake: input.brake,
+            steer: input.steer,
+            handbrake: input.handbrake,
+            boost: input.boost,
+            steer_angle: car.steer_angle,
+            gear: car.gear,
+            reversing: car.reversing,
+            rpm: car.rpm,
+            drift_angle: car.drift_angle,
+            grounded_wheels: car.grounded_wheels,
+            forces: car.forces,
+        };
+        telemetry.push(sample, config.capacity);
+    }
+    telemetry.tick += 1;
+}
+
+fn export_telemetry(telemetry: &Telemetry, config: &TelemetryConfig) {
+    if telemetry.is_empty() {
+        return;
+    }
+
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_millis());
+    let path = config.directory.join(format!("telemetry-{}.{}", stamp, config.format.extension()));
+    match telemetry.export(&path, config.format) {
+        Ok(()) => println!("Telemetry: wrote {} samples to {}", telemetry.len(), path.display()),
+        Err(error) => println!("Telemetry: {}", error),
+    }
+}
+
+fn export_hotkey_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    config: Res<TelemetryConfig>,
+    telemetry: Res<Telemetry>,
+) {
+    if keyboard_input.just_pressed(config.export_key) {
+        export_telemetry(&telemetry, &config);
+    }
+}
+
+fn export_session(config: Res<TelemetryConfig>, telemetry: Res<Telemetry>) {
+    if config.export_on_session_end {
+        export_telemetry(&telemetry, &config);
+    }
+}
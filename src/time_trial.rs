@@ -0,0 +1,427 @@
+use crate::*;
+use crate::menu::{GameMode, GameSettings, GameState};
+use crate::car::{Car, CameraTarget, WheelSuspension};
+use crate::damage::CarDamage;
+use crate::input::{CarInput, CarInputSet};
+use crate::lap_timing::{LapCompleted, LapTimer, format_lap_time};
+use crate::recovery::{CarRecovery, CarReset};
+use crate::save_file::{SaveFileError, load_ron, save_ron};
+use crate::simulation::CarSimulationSet;
+use crate::track_path::{ActiveTrackPath, TrackPath};
+use crate::world::GameEntity;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Metres between the lap times a personal best keeps for the live delta
+pub const TRACE_SPACING: f32 = 5.0;
+
+pub struct TimeTrialPlugin;
+
+impl Plugin for TimeTrialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeTrialConfig>()
+            .init_resource::<PersonalBests>()
+            .add_event::<RestartTimeTrial>()
+            .add_event::<PersonalBestSet>()
+            .add_computed_state::<TimeTrialSession>()
+            .add_sub_state::<TimeTrialState>()
+            .add_systems(Startup, load_personal_bests)
+            .add_systems(OnEnter(TimeTrialSession), (start_session, setup_time_trial_hud))
+            .add_systems(OnExit(TimeTrialSession), end_session)
+            .add_systems(Update, (
+                restart_input_system.run_if(resource_exists::<ButtonInput<KeyCode>>),
+                restart_system,
+                time_trial_hud_system,
+            ).chain().run_if(in_state(TimeTrialSession)))
+            .add_systems(FixedUpdate, (place_on_grid, countdown_system)
+                .chain()
+                .after(CarInputSet)
+                .before(CarSimulationSet)
+                .run_if(in_state(TimeTrialState::PreStart)))
+            .add_systems(FixedUpdate, hold_car_system
+                .after(CarInputSet)
+                .before(CarSimulationSet)
+                .run_if(in_state(TimeTrialState::PreStart).or(in_state(TimeTrialState::Finished))))
+            .add_systems(FixedPostUpdate, time_trial_system.run_if(in_state(TimeTrialState::Running)));
+    }
+}
+
+/// Rules for a run and where personal bests are kept
+#[derive(Resource, Clone)]
+pub struct TimeTrialConfig {
+    pub laps: usize, // Laps in a run, counted or not
+    pub countdown: f32, // Seconds held on the grid before the car is released
+    pub run_up: f32, // Metres before the start line the car is released from - the clock starts at the line
+    pub reset_penalty: f32, // Seconds added to the lap for each reset once the clock is running
+    pub restart_key: KeyCode,
+    pub restart_button: GamepadButton,
+    pub records_path: PathBuf, // Relative to the working directory
+}
+
+impl Default for TimeTrialConfig {
+    fn default() -> Self {
+        Self {
+            laps: 3,
+            countdown: 3.0,
+            run_up: 40.0, // Far enough back to be up to speed at the line on the loop
+            reset_penalty: 3.0,
+            restart_key: KeyCode::Backspace,
+            restart_button: GamepadButton::DPadDown, // Face buttons and shoulders are taken by the car
+            records_path: PathBuf::from("saves/personal_bests.ron"),
+        }
+    }
+}
+
+/// Exists while a time trial is being driven
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TimeTrialSession;
+
+impl ComputedStates for TimeTrialSession {
+    type SourceStates = (GameState, GameMode);
+
+    fn compute((state, mode): (GameState, GameMode)) -> Option<Self> {
+        (state == GameState::InGame && mode == GameMode::TimeTrial).then_some(TimeTrialSession)
+    }
+}
+
+/// Where the run is up to
+#[derive(SubStates, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[source(TimeTrialSession = TimeTrialSession)]
+pub enum TimeTrialState {
+    #[default]
+    PreStart, // Held on the grid through the countdown
+    Running, // Released - the clock starts at the line
+    Finished, // Every lap done, results up
+}
+
+/// Put the player back on the grid and start the run again, from any point in it
+#[derive(Event, Clone, Copy, Debug, Default)]
+pub struct RestartTimeTrial;
+
+/// Sent when a valid lap beats the personal best for its track and car, after it has been saved
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PersonalBestSet {
+    pub car: Entity,
+    pub time: f32,
+}
+
+/// The run being driven
+#[derive(Resource, Clone, Debug, Default)]
+pub struct TimeTrial {
+    pub car: String, // Roster id - personal bests are kept per track and car
+    pub track: String, // Track path name, known once the car is on the grid
+    pub placed: bool, // The car is on the grid for this run
+    pub countdown: f32, // Seconds left before the car is released
+    pub laps: Vec<LapCompleted>, // This run's laps, counted or not
+    pub penalties: f32, // Seconds added for resets this run
+    pub new_record: bool, // This run set a personal best
+    pub lap_distance: f32, // Metres driven since the start line this lap
+    pub last_progress: f32, // Track path distance at the last tick
+    pub trace: Vec<f32>, // Lap time every `TRACE_SPACING` metres of this lap
+    pub delta: Option<f32>, // Seconds behind the personal best at this point of the lap, negative when ahead
+}
+
+impl TimeTrial {
+    fn restart(&mut self) {
+        *self = TimeTrial { car: std::mem::take(&mut self.car), ..default() };
+    }
+}
+
+/// Quickest valid lap for one car on one track
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PersonalBest {
+    pub track: String,
+    pub car: String,
+    pub time: f32,
+    pub sectors: Vec<f32>,
+    #[serde(default)]
+    pub trace: Vec<f32>, // Lap time every `TRACE_SPACING` metres from the start line
+}
+
+/// Every personal best set on this machine - saved whenever one falls
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PersonalBests {
+    pub records: Vec<PersonalBest>,
+}
+
+impl PersonalBests {
+    pub fn get(&self, track: &str, car: &str) -> Option<&PersonalBest> {
+        self.records.iter().find(|record| record.track == track && record.car == car)
+    }
+
+    /// Keep the lap if it beats the record for its track and car, or there isn't one yet
+    pub fn submit(&mut self, lap: PersonalBest) -> bool {
+        match self.records.iter_mut().find(|record| record.track == lap.track && record.car == lap.car) {
+            Some(record) if lap.time < record.time => *record = lap,
+            Some(_) => return false,
+            None => self.records.push(lap),
+        }
+        true
+    }
+
+    /// A missing file is an empty table - nothing has been set yet
+    pub fn load(path: &Path) -> Result<Self, SaveFileError> {
+        Ok(load_ron(path)?.unwrap_or_default())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SaveFileError> {
+        save_ron(self, path, ron::ser::PrettyConfig::default())
+    }
+}
+
+#[derive(Component)]
+pub struct TimeTrialText;
+
+fn load_personal_bests(config: Res<TimeTrialConfig>, mut records: ResMut<PersonalBests>) {
+    match PersonalBests::load(&config.records_path) {
+        Ok(loaded) => *records = loaded,
+        Err(error) => println!("Warning: {}", error),
+    }
+}
+
+fn start_session(mut commands: Commands, settings: Res<GameSettings>) {
+    commands.insert_resource(TimeTrial { car: settings.car.clone(), ..default() });
+}
+
+fn end_session(mut commands: Commands) {
+    commands.remove_resource::<TimeTrial>();
+}
+
+fn setup_time_trial_hud(mut commands: Commands) {
+    // Top-centre banner for the countdown, lap count and results
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 28.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            top: Val::Px(20.0),
+            ..default()
+        },
+        TimeTrialText,
+        GameEntity, // Mark for cleanup
+    ));
+}
+
+fn restart_input_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    config: Res<TimeTrialConfig>,
+    mut restart_events: EventWriter<RestartTimeTrial>,
+) {
+    if keyboard_input.just_pressed(config.restart_key) || gamepads.iter().any(|gamepad| gamepad.just_pressed(config.restart_button)) {
+        restart_events.write(RestartTimeTrial);
+    }
+}
+
+// Setting the state it is already in doesn't rerun OnEnter, so the run is reset here for every restart
+fn restart_system(
+    mut restart_events: EventReader<RestartTimeTrial>,
+    mut trial: ResMut<TimeTrial>,
+    mut next_state: ResMut<NextState<TimeTrialState>>,
+) {
+    if restart_events.read().count() == 0 {
+        return;
+    }
+    trial.restart();
+    next_state.set(TimeTrialState::PreStart);
+    println!("Time trial restarted");
+}
+
+// Line the player's car up `run_up` metres before the start line, as fresh as it left the garage
+#[allow(clippy::type_complexity)]
+fn place_on_grid(
+    config: Res<TimeTrialConfig>,
+    mut trial: ResMut<TimeTrial>,
+    active_path: Option<Res<ActiveTrackPath>>,
+    paths: Res<Assets<TrackPath>>,
+    mut car_query: Query<(Entity, &mut Transform, &mut Velocity, &mut Car, &mut LapTimer, Option<&mut CarRecovery>, Option<&mut CarDamage>), With<CameraTarget>>,
+    mut wheel_query: Query<&mut WheelSuspension>,
+) {
+    if trial.placed {
+        return;
+    }
+    let Some(path) = active_path.and_then(|active_path| paths.get(&active_path.0)) else {
+        return; // Still loading
+    };
+    let Ok((entity, mut transform, mut velocity, mut car, mut timer, recovery, damage)) = car_query.single_mut() else {
+        return;
+    };
+
+    let sample = path.sample(path.point_distance(path.start_line) - config.run_up);
+    let grid = Transform::from_translation(sample.position + Vec3::Y * 0.7).looking_to(sample.direction, Vec3::Y);
+    *transform = grid;
+    *velocity = Velocity::zero();
+    car.speed = 0.0;
+    car.wheel_speed = 0.0;
+    car.gear = 1;
+    car.reversing = false;
+    car.shift_timer = 0.0;
+    car.fuel = car.engine.fuel_capacity;
+    car.tire_wear = 0.0;
+    car.boost_meter = Car::default().boost_meter;
+    for mut wheel in wheel_query.iter_mut().filter(|wheel| wheel.car == entity) {
+        wheel.angular_velocity = 0.0;
+        wheel.wear = 0.0;
+    }
+    *timer = LapTimer::default();
+    if let Some(mut recovery) = recovery {
+        recovery.checkpoint = Some(grid); // Resetting before the line goes back to the grid
+    }
+    if let Some(mut damage) = damage {
+        *damage = CarDamage::default();
+    }
+
+    trial.placed = true;
+    trial.track = path.name.clone();
+    trial.countdown = config.countdown;
+    trial.last_progress = sample.distance;
+    println!("Time trial: {} on {}, {} laps", trial.car, trial.track, config.laps);
+}
+
+fn countdown_system(
+    time: Res<Time>,
+    mut trial: ResMut<TimeTrial>,
+    mut next_state: ResMut<NextState<TimeTrialState>>,
+) {
+    if !trial.placed {
+        return;
+    }
+    trial.countdown = (trial.countdown - time.delta_secs()).max(0.0);
+    if trial.countdown <= 0.0 {
+        next_state.set(TimeTrialState::Running);
+    }
+}
+
+// Keep the car on the handbrake through the countdown and once the run is over
+fn hold_car_system(mut car_query: Query<&mut CarInput, With<CameraTarget>>) {
+    for mut input in car_query.iter_mut() {
+        *input = CarInput { handbrake: true, ..default() };
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn time_trial_system(
+    config: Res<TimeTrialConfig>,
+    mut trial: ResMut<TimeTrial>,
+    mut records: ResMut<PersonalBests>,
+    active_path: Option<Res<ActiveTrackPath>>,
+    paths: Res<Assets<TrackPath>>,
+    mut lap_events: EventReader<LapCompleted>,
+    mut reset_events: EventReader<CarReset>,
+    mut best_events: EventWriter<PersonalBestSet>,
+    mut car_query: Query<(Entity, &Transform, &mut LapTimer), With<CameraTarget>>,
+    mut next_state: ResMut<NextState<TimeTrialState>>,
+) {
+    let Some(path) = active_path.and_then(|active_path| paths.get(&active_path.0)) else {
+        return;
+    };
+    let Ok((entity, transform, mut timer)) = car_query.single_mut() else {
+        return;
+    };
+
+    // Resets cost time once the clock is running
+    for _ in reset_events.read().filter(|reset| reset.car == entity) {
+        if timer.started {
+            timer.current += config.reset_penalty;
+            trial.penalties += config.reset_penalty;
+            println!("Reset penalty: +{:.1}s", config.reset_penalty);
+        }
+    }
+
+    for lap in lap_events.read().filter(|lap| lap.car == entity) {
+        if lap.valid {
+            let record = PersonalBest {
+                track: trial.track.clone(),
+                car: trial.car.clone(),
+                time: lap.time,
+                sectors: lap.sectors.clone(),
+                trace: trial.trace.clone(),
+            };
+            if records.submit(record) {
+                trial.new_record = true;
+                println!("New personal best: {}", format_lap_time(lap.time));
+                if let Err(error) = records.save(&config.records_path) {
+                    println!("Warning: {}", error);
+                }
+                best_events.write(PersonalBestSet { car: entity, time: lap.time });
+            }
+        }
+        trial.laps.push(lap.clone());
+    }
+
+    if trial.laps.len() >= config.laps {
+        timer.started = false; // Stop the clock on the last lap
+        trial.delta = None;
+        next_state.set(TimeTrialState::Finished);
+        println!("Time trial finished");
+        return;
+    }
+
+    // Add up the distance tick by tick so it runs on past the end of the path rather than wrapping at the line
+    let progress = path.progress(transform.translation);
+    if timer.started && timer.current == 0.0 {
+        // Crossed the line this tick - the gate fires before the middle of the car reaches it
+        trial.lap_distance = path.delta(path.point_distance(path.start_line), progress);
+        trial.trace.clear();
+    } else {
+        trial.lap_distance += path.delta(trial.last_progress, progress);
+    }
+    trial.last_progress = progress;
+    if !timer.started {
+        trial.delta = None; // Still on the run-up
+        return;
+    }
+    while trial.lap_distance >= trial.trace.len() as f32 * TRACE_SPACING {
+        trial.trace.push(timer.current);
+    }
+
+    // Against the personal best at the same point of its lap, from when the car is fully over the line
+    let delta = records.get(&trial.track, &trial.car).filter(|_| trial.lap_distance >= 0.0).and_then(|best| {
+        let position = trial.lap_distance / TRACE_SPACING;
+        let index = position as usize;
+        let (from, to) = (*best.trace.get(index)?, *best.trace.get(index + 1)?);
+        Some(timer.current - (from + (to - from) * position.fract()))
+    });
+    trial.delta = delta;
+}
+
+fn time_trial_hud_system(
+    config: Res<TimeTrialConfig>,
+    trial: Res<TimeTrial>,
+    records: Res<PersonalBests>,
+    state: Res<State<TimeTrialState>>,
+    mut text_query: Query<&mut Text, With<TimeTrialText>>,
+) {
+    let Ok(mut text) = text_query.single_mut() else {
+        return;
+    };
+    let record = records.get(&trial.track, &trial.car).map_or("-".to_string(), |record| format_lap_time(record.time));
+    let penalties = if trial.penalties > 0.0 { format!("  PENALTIES +{:.1}s", trial.penalties) } else { String::new() };
+
+    text.0 = match state.get() {
+        TimeTrialState::PreStart if !trial.placed => String::new(),
+        TimeTrialState::PreStart => format!("TIME TRIAL  PB {}\n{}", record, trial.countdown.ceil()),
+        TimeTrialState::Running => {
+            let lap = (trial.laps.len() + 1).min(config.laps);
+            format!("LAP {}/{}  PB {}{}", lap, config.laps, record, penalties)
+        }
+        TimeTrialState::Finished => {
+            let mut results = "FINISHED\n".to_string();
+            for (number, lap) in trial.laps.iter().enumerate() {
+                let invalid = if lap.valid { "" } else { "  INVALID" };
+                let _ = writeln!(results, "LAP {}  {}{}", number + 1, format_lap_time(lap.time), invalid);
+            }
+            let new_record = if trial.new_record { "  NEW!" } else { "" };
+            let restart = format!("{:?}", config.restart_key).to_uppercase();
+            let _ = write!(results, "PB {}{}{}\n{} TO GO AGAIN", record, new_record, penalties, restart);
+            results
+        }
+    };
+}
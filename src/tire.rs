@@ -0,0 +1,86 @@
+use crate::*;
+use serde::Deserialize;
+
+/// Tyre grip characteristics, independent of the car and the surface it drives on.
+///
+/// Forces follow a simplified Pacejka "magic formula" on the combined slip vector, so the
+/// longitudinal and lateral forces always share one friction circle of radius
+/// `peak_friction * surface_friction * load`.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct TireSpec {
+    pub peak_friction: f32, // Friction coefficient at peak slip on a surface with friction 1.0
+    pub peak_slip_ratio: f32, // Longitudinal slip where grip peaks
+    pub peak_slip_angle: f32, // Lateral slip (radians) where grip peaks
+    pub shape: f32, // Magic formula C - higher values lose more grip once past the peak
+    pub low_speed: f32, // Slip denominators never drop below this (m/s) so a parked car stays calm
+    pub wheel_inertia: f32, // Rotational inertia of wheel and tyre (kg m^2)
+    pub wear_per_km: f32, // Tread lost per kilometre rolled
+    pub slide_wear: f32, // Tread lost per metre the contact patch slides
+    pub worn_grip: f32, // Share of grip left on a bald tyre
+}
+
+impl Default for TireSpec {
+    fn default() -> Self {
+        Self {
+            peak_friction: 1.8, // Grippy arcade tyre - real road tyres sit around 1.0-1.2
+            peak_slip_ratio: 0.1,
+            peak_slip_angle: 0.14, // ~8 degrees
+            shape: 1.4, // Sliding grip settles at ~80% of peak
+            low_speed: 5.0,
+            wheel_inertia: 1.0,
+            wear_per_km: 0.01, // A long race distance on a gentle driver
+            slide_wear: 0.0001, // Under an hour of hard driving finishes a set
+            worn_grip: 0.7,
+        }
+    }
+}
+
+impl TireSpec {
+    /// How much faster the tread moves than the road: 0.0 rolling freely, positive spinning, -1.0 locked
+    pub fn slip_ratio(&self, wheel_speed: f32, ground_speed: f32) -> f32 {
+        (wheel_speed - ground_speed) / ground_speed.abs().max(self.low_speed)
+    }
+
+    /// Angle between where the tyre points and where the contact patch travels, positive sliding right
+    pub fn slip_angle(&self, longitudinal_velocity: f32, lateral_velocity: f32) -> f32 {
+        (lateral_velocity / longitudinal_velocity.abs().max(self.low_speed)).atan()
+    }
+
+    /// Tread lost per second while rolling and sliding at the given speeds (m/s)
+    pub fn wear_rate(&self, rolling_speed: f32, sliding_speed: f32) -> f32 {
+        rolling_speed * self.wear_per_km / 1000.0 + sliding_speed * self.slide_wear
+    }
+
+    /// Grip multiplier for a tyre with this much wear - falls linearly to `worn_grip` when bald
+    pub fn wear_grip(&self, wear: f32) -> f32 {
+        1.0 - (1.0 - self.worn_grip) * wear.clamp(0.0, 1.0)
+    }
+
+    /// Fraction of peak grip produced at a slip normalised so the peak sits at 1.0
+    pub fn grip_curve(&self, normalized_slip: f32) -> f32 {
+        // Choose B so that C * atan(B) reaches PI/2 - the sine peaks - exactly at 1.0
+        let stiffness = (PI / (2.0 * self.shape)).tan();
+        (self.shape * (stiffness * normalized_slip).atan()).sin()
+    }
+
+    /// Longitudinal force per unit slip ratio near zero slip, used to integrate wheel spin stably
+    pub fn longitudinal_stiffness(&self, load: f32, surface_friction: f32) -> f32 {
+        let stiffness = (PI / (2.0 * self.shape)).tan();
+        self.peak_friction * surface_friction * load * self.shape * stiffness / self.peak_slip_ratio
+    }
+
+    /// Tyre force as (longitudinal, lateral) in the wheel's frame for a given slip, load (N) and surface
+    pub fn force(&self, slip_ratio: f32, slip_angle: f32, load: f32, surface_friction: f32) -> Vec2 {
+        // Normalise each slip by its peak so both directions saturate together
+        let slip = Vec2::new(slip_ratio / self.peak_slip_ratio, slip_angle / self.peak_slip_angle);
+        let combined_slip = slip.length();
+        if combined_slip <= f32::EPSILON || load <= 0.0 {
+            return Vec2::ZERO;
+        }
+
+        // Total force follows the curve, its direction follows the slip - lateral force opposes the slide
+        let magnitude = self.peak_friction * surface_friction * load * self.grip_curve(combined_slip);
+        let direction = slip / combined_slip;
+        Vec2::new(direction.x, -direction.y) * magnitude
+    }
+}
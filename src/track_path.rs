@@ -0,0 +1,353 @@
+use crate::*;
+use bevy::asset::{AssetLoader, LoadContext, io::Reader};
+use serde::Deserialize;
+use thiserror::Error;
+
+pub struct TrackPathPlugin;
+
+impl Plugin for TrackPathPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<TrackPath>()
+            .init_asset_loader::<TrackPathLoader>()
+            .add_systems(Startup, load_track_path);
+    }
+}
+
+/// Path the current track is laid out along
+pub const TRACK_PATH: &str = "tracks/loop.path.ron";
+
+/// The path cars race along this session - AI, lap progress and anything else that needs the layout reads it
+#[derive(Resource)]
+pub struct ActiveTrackPath(pub Handle<TrackPath>);
+
+/// Control point of a path, as written in a `.path.ron` file
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct PathPoint {
+    pub position: [f32; 3], // On the road surface
+    pub target_speed: f32, // m/s the section is meant to be driven at
+    pub width: f32, // Road width (m) centred on the path
+    #[serde(default)]
+    pub banking: f32, // Road roll (radians), positive leans the road into a left-hander
+}
+
+/// File layout of a `.path.ron` file
+#[derive(Deserialize)]
+struct TrackPathFile {
+    name: String,
+    #[serde(default = "default_closed")]
+    closed: bool,
+    #[serde(default = "default_resolution")]
+    resolution: f32,
+    points: Vec<PathPoint>,
+    #[serde(default)]
+    start_line: usize,
+    #[serde(default)]
+    checkpoints: Vec<usize>,
+}
+
+fn default_closed() -> bool {
+    true
+}
+
+fn default_resolution() -> f32 {
+    1.0
+}
+
+/// Point on the baked path with everything interpolated from the control points either side
+#[derive(Clone, Copy, Debug)]
+pub struct PathSample {
+    pub distance: f32, // Along the path from the first control point
+    pub position: Vec3,
+    pub direction: Vec3, // Unit tangent in driving order
+    pub curvature: f32, // 1 / radius, positive bending left
+    pub target_speed: f32,
+    pub width: f32,
+    pub banking: f32,
+}
+
+impl PathSample {
+    /// Unit vector across the road towards the right-hand edge
+    pub fn right(&self) -> Vec3 {
+        self.direction.cross(Vec3::Y).normalize_or(Vec3::X)
+    }
+}
+
+/// Where a position sits relative to the path
+#[derive(Clone, Copy, Debug)]
+pub struct PathProjection {
+    pub distance: f32, // Along the path of the closest point
+    pub position: Vec3, // Closest point on the path
+    pub lateral: f32, // Sideways offset (m), positive right of the path
+}
+
+/// Ordered spline through control points, loaded from a `.path.ron` file and baked into evenly spaced
+/// samples. Saved edits reload onto anything reading it.
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct TrackPath {
+    pub name: String,
+    pub closed: bool, // Loops back from the last control point to the first
+    pub points: Vec<PathPoint>,
+    pub start_line: usize, // Control point the start/finish line crosses the road at
+    pub checkpoints: Vec<usize>, // Control points of the gates between, in driving order
+    samples: Vec<PathSample>,
+    point_distances: Vec<f32>, // Distance along the path of each control point
+    length: f32,
+}
+
+impl TrackPath {
+    /// Bake a centripetal Catmull-Rom spline through `points`, sampled every `resolution` metres or so
+    pub fn new(name: impl Into<String>, points: Vec<PathPoint>, closed: bool, resolution: f32) -> Self {
+        let count = points.len();
+        let control = |index: isize| -> PathPoint {
+            if closed {
+                return points[index.rem_euclid(count as isize) as usize];
+            }
+            // Open ends carry on in a straight line past the first and last points
+            let clamped = index.clamp(0, count as isize - 1) as usize;
+            let overshoot = index - clamped as isize;
+            let mut point = points[clamped];
+            if overshoot != 0 && count > 1 {
+                let inner = Vec3::from(points[if overshoot < 0 { 1 } else { count - 2 }].position);
+                point.position = (Vec3::from(point.position) * 2.0 - inner).to_array();
+            }
+            point
+        };
+
+        let mut samples: Vec<PathSample> = Vec::new();
+        let mut first_samples = Vec::with_capacity(count);
+        let segments = if closed { count } else { count.saturating_sub(1) };
+        for segment in 0..segments {
+            first_samples.push(samples.len());
+            let index = segment as isize;
+            let (p0, p1, p2, p3) = (control(index - 1), control(index), control(index + 1), control(index + 2));
+            let chord = Vec3::from(p1.position).distance(Vec3::from(p2.position));
+            let steps = (chord / resolution.max(0.1)).ceil().max(1.0) as usize;
+            for step in 0..steps {
+                let t = step as f32 / steps as f32;
+                samples.push(Self::interpolate(p0, p1, p2, p3, t));
+            }
+        }
+        if let Some(&last) = points.last().filter(|_| !closed) {
+            first_samples.push(samples.len());
+            samples.push(Self::interpolate(last, last, last, last, 0.0));
+        }
+        if samples.is_empty() {
+            first_samples.push(0);
+            samples.extend(points.first().map(|&point| Self::interpolate(point, point, point, point, 0.0)));
+        }
+
+        // Distances, tangents and curvature from each sample's neighbours
+        let mut length = 0.0;
+        for index in 0..samples.len() {
+            samples[index].distance = length;
+            if let Some(next) = Self::neighbour(&samples, index, 1, closed) {
+                length += samples[index].position.distance(next.position);
+            }
+        }
+        let tangents: Vec<(Vec3, f32)> = (0..samples.len())
+            .map(|index| {
+                let here = samples[index].position;
+                let before = Self::neighbour(&samples, index, -1, closed).map_or(here, |sample| sample.position);
+                let after = Self::neighbour(&samples, index, 1, closed).map_or(here, |sample| sample.position);
+                let (incoming, outgoing) = ((here - before).with_y(0.0), (after - here).with_y(0.0));
+                let direction = (after - before).normalize_or(Vec3::NEG_Z);
+                if incoming.length() < f32::EPSILON || outgoing.length() < f32::EPSILON {
+                    return (direction, 0.0);
+                }
+                let turn = incoming.angle_between(outgoing) * incoming.cross(outgoing).y.signum();
+                (direction, turn / ((incoming.length() + outgoing.length()) * 0.5))
+            })
+            .collect();
+        for (sample, (direction, curvature)) in samples.iter_mut().zip(tangents) {
+            sample.direction = direction;
+            sample.curvature = curvature;
+        }
+
+        let point_distances = first_samples.iter().map(|&index| samples.get(index).map_or(0.0, |sample| sample.distance)).collect();
+        Self { name: name.into(), closed, points, start_line: 0, checkpoints: Vec::new(), samples, point_distances, length }
+    }
+
+    // Barry-Goldman evaluation between p1 and p2 - the centripetal spacing keeps tight corners from looping
+    fn interpolate(p0: PathPoint, p1: PathPoint, p2: PathPoint, p3: PathPoint, t: f32) -> PathSample {
+        let [a, b, c, d] = [p0, p1, p2, p3].map(|point| Vec3::from(point.position));
+        let knot = |from: Vec3, to: Vec3| from.distance(to).sqrt().max(1e-4);
+        let (t0, t1) = (0.0, knot(a, b));
+        let t2 = t1 + knot(b, c);
+        let t3 = t2 + knot(c, d);
+        let u = t1 + (t2 - t1) * t;
+        let lerp = |from: Vec3, to: Vec3, start: f32, end: f32| from + (to - from) * ((u - start) / (end - start));
+        let (a1, a2, a3) = (lerp(a, b, t0, t1), lerp(b, c, t1, t2), lerp(c, d, t2, t3));
+        let (b1, b2) = (lerp(a1, a2, t0, t2), lerp(a2, a3, t1, t3));
+
+        PathSample {
+            distance: 0.0,
+            position: lerp(b1, b2, t1, t2),
+            direction: Vec3::NEG_Z,
+            curvature: 0.0,
+            target_speed: p1.target_speed + (p2.target_speed - p1.target_speed) * t,
+            width: p1.width + (p2.width - p1.width) * t,
+            banking: p1.banking + (p2.banking - p1.banking) * t,
+        }
+    }
+
+    fn neighbour(samples: &[PathSample], index: usize, step: isize, closed: bool) -> Option<&PathSample> {
+        let target = index as isize + step;
+        if closed {
+            samples.get(target.rem_euclid(samples.len() as isize) as usize).filter(|_| samples.len() > 1)
+        } else {
+            usize::try_from(target).ok().and_then(|target| samples.get(target))
+        }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+
+    pub fn samples(&self) -> &[PathSample] {
+        &self.samples
+    }
+
+    /// Distance along the path of a control point
+    pub fn point_distance(&self, index: usize) -> f32 {
+        self.point_distances.get(index).copied().unwrap_or(self.length)
+    }
+
+    /// Distances along the path of the start/finish line followed by every checkpoint, in driving order
+    pub fn gate_distances(&self) -> Vec<f32> {
+        std::iter::once(self.start_line)
+            .chain(self.checkpoints.iter().copied())
+            .map(|index| self.point_distance(index))
+            .collect()
+    }
+
+    /// Bring a distance onto the path - wrapped round a closed path, clamped to the ends of an open one
+    pub fn wrap(&self, distance: f32) -> f32 {
+        if self.closed {
+            distance.rem_euclid(self.length.max(f32::EPSILON))
+        } else {
+            distance.clamp(0.0, self.length)
+        }
+    }
+
+    /// Path properties at a distance along it
+    pub fn sample(&self, distance: f32) -> PathSample {
+        let distance = self.wrap(distance);
+        let index = self.samples.partition_point(|sample| sample.distance <= distance).saturating_sub(1);
+        let start = self.samples[index];
+        let Some(end) = Self::neighbour(&self.samples, index, 1, self.closed) else {
+            return start;
+        };
+        let end_distance = if index + 1 == self.samples.len() { self.length } else { end.distance };
+        let t = ((distance - start.distance) / (end_distance - start.distance).max(f32::EPSILON)).clamp(0.0, 1.0);
+
+        PathSample {
+            distance,
+            position: start.position.lerp(end.position, t),
+            direction: start.direction.lerp(end.direction, t).normalize_or(start.direction),
+            curvature: start.curvature + (end.curvature - start.curvature) * t,
+            target_speed: start.target_speed + (end.target_speed - start.target_speed) * t,
+            width: start.width + (end.width - start.width) * t,
+            banking: start.banking + (end.banking - start.banking) * t,
+        }
+    }
+
+    pub fn point_at(&self, distance: f32) -> Vec3 {
+        self.sample(distance).position
+    }
+
+    pub fn direction_at(&self, distance: f32) -> Vec3 {
+        self.sample(distance).direction
+    }
+
+    /// How sharply the path bends at a distance (1 / radius), positive bending left
+    pub fn curvature_at(&self, distance: f32) -> f32 {
+        self.sample(distance).curvature
+    }
+
+    /// Closest point on the path to `position`, measured across the ground
+    pub fn project(&self, position: Vec3) -> PathProjection {
+        let mut best = (f32::MAX, PathProjection { distance: 0.0, position: Vec3::ZERO, lateral: 0.0 });
+        for (index, start) in self.samples.iter().enumerate() {
+            let end = Self::neighbour(&self.samples, index, 1, self.closed).unwrap_or(start);
+            let segment = end.position - start.position;
+            let flat = segment.with_y(0.0);
+            let t = ((position - start.position).with_y(0.0).dot(flat) / flat.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+            let closest = start.position + segment * t;
+            let gap = (position - closest).with_y(0.0);
+            if gap.length_squared() < best.0 {
+                let right = start.direction.lerp(end.direction, t).cross(Vec3::Y).normalize_or(Vec3::X);
+                best = (gap.length_squared(), PathProjection {
+                    distance: self.wrap(start.distance + segment.length() * t),
+                    position: closest,
+                    lateral: gap.dot(right),
+                });
+            }
+        }
+        best.1
+    }
+
+    /// Distance along the path of the closest point to `position`
+    pub fn progress(&self, position: Vec3) -> f32 {
+        self.project(position).distance
+    }
+
+    /// Signed distance from one point along the path to another - the short way round a closed path
+    pub fn delta(&self, from: f32, to: f32) -> f32 {
+        let delta = to - from;
+        if self.closed && self.length > 0.0 {
+            (delta + self.length * 0.5).rem_euclid(self.length) - self.length * 0.5
+        } else {
+            delta
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TrackPathLoader;
+
+#[derive(Debug, Error)]
+pub enum TrackPathLoaderError {
+    #[error("could not read track path: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse track path: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("track path needs at least two points")]
+    TooFewPoints,
+    #[error("track path gate at point {0} is past the last point")]
+    GateOutOfRange(usize),
+}
+
+impl AssetLoader for TrackPathLoader {
+    type Asset = TrackPath;
+    type Settings = ();
+    type Error = TrackPathLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<TrackPath, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: TrackPathFile = ron::de::from_bytes(&bytes)?;
+        if file.points.len() < 2 {
+            return Err(TrackPathLoaderError::TooFewPoints);
+        }
+        if let Some(&gate) = std::iter::once(&file.start_line).chain(&file.checkpoints).find(|&&gate| gate >= file.points.len()) {
+            return Err(TrackPathLoaderError::GateOutOfRange(gate));
+        }
+        let mut path = TrackPath::new(file.name, file.points, file.closed, file.resolution);
+        path.start_line = file.start_line;
+        path.checkpoints = file.checkpoints;
+        Ok(path)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["path.ron"]
+    }
+}
+
+fn load_track_path(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ActiveTrackPath(asset_server.load(TRACK_PATH)));
+}
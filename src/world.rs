@@ -1,16 +1,23 @@
 use crate::*;
-use crate::car::{Car, CameraTarget, Wheel, FrontWheel};
-use crate::menu::GameState;
+use crate::car::{Car, CameraTarget};
+use crate::car_spec::{CarRoster, CarSpecHandle};
+use crate::menu::{GameState, GameSettings};
+use crate::input::{CarInput, InputSource};
+use crate::surface::SurfaceType;
+use crate::assists::DriverAssists;
+use crate::recovery::CarRecovery;
+use crate::damage::CarDamage;
+use crate::boost::NearMissTracker;
+use crate::simulation::PhysicsInterpolation;
+use crate::lap_timing::LapTimer;
 use crate::post_processing::RacingPostProcessSettings;
 use bevy_rapier3d::prelude::*;
-use bevy::gltf::GltfAssetLabel;
 
 pub struct WorldPlugin;
 
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::InGame), setup_world)
-           .add_systems(Update, setup_car_wheels.run_if(in_state(GameState::InGame)))
            .add_systems(OnExit(GameState::InGame), cleanup_world);
     }
 }
@@ -18,9 +25,6 @@ impl Plugin for WorldPlugin {
 #[derive(Component)]
 pub struct GameEntity;
 
-#[derive(Component)]
-pub struct CarModel;
-
 fn cleanup_world(
     mut commands: Commands,
     game_entities: Query<Entity, With<GameEntity>>,
@@ -47,17 +51,20 @@ fn cleanup_world(
     commands.insert_resource(ClearColor(Color::srgb(0.1, 0.1, 0.2)));
 }
 
-fn setup_world(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<StandardMaterial>>, asset_server: Res<AssetServer>) {
+fn setup_world(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<StandardMaterial>>, roster: Res<CarRoster>, settings: Res<GameSettings>) {
     // Set game background color
     commands.insert_resource(ClearColor(Color::srgb(0.5, 0.8, 1.0))); // Blue sky for game
     
-    // Add large ground plane
+    // Add large ground plane with a road, run-off and an ice patch laid on top
     spawn_ground(&mut commands, &mut meshes, &mut materials);
+    spawn_track_surfaces(&mut commands, &mut meshes, &mut materials);
     
     // Camera is handled by CameraPlugin - don't duplicate here
     
-    // Spawn car with GLB model
-    let _car_entity = spawn_car(&mut commands, &mut materials, &asset_server);
+    // Spawn the player's chosen car, raised so the suspension settles it onto the ground
+    if let Some(car) = spawn_car(&mut commands, &roster, &settings.car, Transform::from_xyz(0.0, 0.7, 0.0)) {
+        commands.entity(car).insert(CameraTarget);
+    }
     
     // Create track markers and obstacles
     spawn_track_markers(&mut commands, &mut meshes, &mut materials);
@@ -85,150 +92,92 @@ fn spawn_ground(
         // Physics components
         RigidBody::Fixed,
         Collider::cuboid(150.0, 0.1, 150.0), // Large flat collider matching the bigger ground
-        Friction::coefficient(0.3), // Reduced friction for easier car movement
+        Friction::coefficient(0.7), // Body contact only - tyre grip comes from SurfaceType
+        SurfaceType::Grass, // Everything off the road is grass verge
         GameEntity, // Mark for cleanup
     ));
 }
 
-fn spawn_car(
+fn spawn_track_surfaces(
     commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
     materials: &mut Assets<StandardMaterial>,
-    asset_server: &AssetServer,
-) -> Entity {
-    // Load the GLB car model
-    let car_scene = asset_server.load(GltfAssetLabel::Scene(0).from_asset("cars/sedan-sports.glb"));
-    
-    // Spawn the car entity with physics and game components
-    let car_entity = commands
+) {
+    // Thin slabs just above the ground collider so the suspension rays hit them first
+    let slab_height = 0.02;
+    let slab_y = 0.1;
+    let mut spawn_slab = |center: Vec2, size: Vec2, surface: SurfaceType, color: Color| {
+        commands.spawn((
+            Mesh3d(meshes.add(Cuboid::new(size.x, slab_height, size.y))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: color,
+                perceptual_roughness: 0.9,
+                ..default()
+            })),
+            Transform::from_xyz(center.x, slab_y, center.y),
+            RigidBody::Fixed,
+            Collider::cuboid(size.x / 2.0, slab_height / 2.0, size.y / 2.0),
+            Friction::coefficient(0.7),
+            surface,
+            GameEntity, // Mark for cleanup
+        ));
+    };
+
+    // Square asphalt loop around the track markers
+    let asphalt = Color::srgb(0.2, 0.2, 0.22);
+    let loop_half_size = 21.0;
+    let road_width = 8.0;
+    let road_length = loop_half_size * 2.0 + road_width;
+    spawn_slab(Vec2::new(0.0, -loop_half_size), Vec2::new(road_length, road_width), SurfaceType::Asphalt, asphalt);
+    spawn_slab(Vec2::new(0.0, loop_half_size), Vec2::new(road_length, road_width), SurfaceType::Asphalt, asphalt);
+    spawn_slab(Vec2::new(-loop_half_size, 0.0), Vec2::new(road_width, road_length), SurfaceType::Asphalt, asphalt);
+    spawn_slab(Vec2::new(loop_half_size, 0.0), Vec2::new(road_width, road_length), SurfaceType::Asphalt, asphalt);
+
+    // Gravel traps on the outside of the north and south straights
+    let gravel = Color::srgb(0.75, 0.68, 0.52);
+    let trap_offset = loop_half_size + road_width / 2.0 + 4.0;
+    spawn_slab(Vec2::new(0.0, -trap_offset), Vec2::new(20.0, 8.0), SurfaceType::Gravel, gravel);
+    spawn_slab(Vec2::new(0.0, trap_offset), Vec2::new(20.0, 8.0), SurfaceType::Gravel, gravel);
+
+    // Frozen patch off to the south for sliding about
+    spawn_slab(Vec2::new(0.0, 50.0), Vec2::new(12.0, 12.0), SurfaceType::Ice, Color::srgb(0.8, 0.9, 1.0));
+}
+
+/// Spawn a car from the roster - the body shape, model, lights and tuning are filled in from its
+/// spec by apply_car_specs once the spec has loaded. Returns `None` for an unknown roster id.
+pub fn spawn_car(commands: &mut Commands, roster: &CarRoster, car_id: &str, transform: Transform) -> Option<Entity> {
+    let Some(spec) = roster.spec(car_id) else {
+        println!("Warning: no car '{}' in the roster", car_id);
+        return None;
+    };
+
+    let car = commands
         .spawn((
-            // Start with just the transform and physics - no visual model yet
-            Transform::from_xyz(0.0, 0.7, 0.0), // Raised higher to account for GLB model height
+            transform,
             Visibility::default(), // Add visibility component to prevent warnings
             Car::default(),
-            CameraTarget,
-            CarModel, // Mark to identify this as the car model for wheel setup
+            CarSpecHandle(spec.clone()),
+            CarInput::default(),
+            InputSource::Keyboard, // Callers swap this for AI, replay or network cars
+            DriverAssists::default(), // Levels follow GameSettings for player cars
             GameEntity, // Mark for cleanup
         ))
         .insert((
-            // Physics components - BMW M-series sedan properties
             RigidBody::Dynamic,
-            Collider::cuboid(0.95, 0.7, 2.4), // BMW M-series dimensions: ~1.9m wide, 1.4m tall, 4.8m long
-            AdditionalMassProperties::Mass(800.0), // Reduced mass for better game responsiveness
+            ReadMassProperties::default(),
             ExternalForce::default(),
             ExternalImpulse::default(),
             Velocity::default(),
-            Friction::coefficient(3.5), // Much higher friction to prevent skidding
-            Restitution::coefficient(0.02), // Very minimal bounce
-            Damping { linear_damping: 0.1, angular_damping: 1.0 }, // Lower damping for better responsiveness
-            LockedAxes::ROTATION_LOCKED_X | LockedAxes::ROTATION_LOCKED_Z, // Prevent flipping
+            ActiveEvents::CONTACT_FORCE_EVENTS, // Impacts drive gamepad rumble
+            ContactForceEventThreshold(20000.0), // Ignore gentle nudges
+            CarRecovery::default(),
+            CarDamage::default(),
+            NearMissTracker::default(),
+            PhysicsInterpolation::default(), // Drawn between fixed ticks
+            LapTimer::default(),
         ))
-        .with_children(|parent| {
-            // Add the GLB model as a child with offset to align with physics collider
-            parent.spawn((
-                SceneRoot(car_scene),
-                Transform::from_xyz(0.0, -0.7, 0.0), // Move model down to align with collider
-            ));
-
-            // Add headlights to the car
-            let _headlight_material = materials.add(StandardMaterial {
-                base_color: Color::srgb(1.0, 1.0, 0.9), // Warm white
-                emissive: LinearRgba::new(1.0, 1.0, 0.9, 0.0),
-                ..default()
-            });
-
-            // Left headlight
-            parent.spawn((
-                SpotLight {
-                    intensity: 5_000_000.0, // Much brighter headlight 
-                    color: Color::srgb(1.0, 1.0, 0.9), // Warm white
-                    shadows_enabled: true,
-                    inner_angle: PI / 8.0, // 22.5 degrees inner cone
-                    outer_angle: PI / 4.0, // 45 degrees outer cone
-                    range: 400.0, // Much longer range for nighttime driving
-                    ..default()
-                },
-                Transform::from_xyz(-0.5, 0.0, -1.2) // Adjusted Y to account for model offset
-                    .looking_at(Vec3::new(-0.5, 0.0, -20.0), Vec3::Y), // Point forward
-            ));
-
-            // Right headlight
-            parent.spawn((
-                SpotLight {
-                    intensity: 5_000_000.0, // Much brighter headlight
-                    color: Color::srgb(1.0, 1.0, 0.9), // Warm white
-                    shadows_enabled: true,
-                    inner_angle: PI / 8.0, // 22.5 degrees inner cone
-                    outer_angle: PI / 4.0, // 45 degrees outer cone
-                    range: 400.0, // Much longer range for nighttime driving
-                    ..default()
-                },
-                Transform::from_xyz(0.5, 0.0, -1.2) // Adjusted Y to account for model offset
-                    .looking_at(Vec3::new(0.5, 0.0, -20.0), Vec3::Y), // Point forward
-            ));
-        })
         .id();
-
-    car_entity
-}
-
-// System to find and mark wheel entities by name
-fn setup_car_wheels(
-    mut commands: Commands,
-    car_query: Query<Entity, (With<CarModel>, Without<Wheel>)>,
-    children: Query<&Children>,
-    names: Query<&Name>,
-    wheel_query: Query<Entity, With<Wheel>>,
-) {
-    // Check if we already have wheels marked
-    if wheel_query.iter().count() >= 4 {
-        return; // All wheels already marked, no need to continue
-    }
-
-    for car_entity in car_query.iter() {
-        if let Ok(car_children) = children.get(car_entity) {
-            mark_wheels_recursive(&mut commands, car_children, &children, &names, &wheel_query);
-        }
-    }
-}
-
-fn mark_wheels_recursive(
-    commands: &mut Commands,
-    entities: &Children,
-    children: &Query<&Children>,
-    names: &Query<&Name>,
-    existing_wheels: &Query<Entity, With<Wheel>>,
-) {
-    for entity in entities.iter() {
-        // Skip if this entity is already marked as a wheel
-        if existing_wheels.get(entity).is_ok() {
-            continue;
-        }
-
-        // Check if this entity has a name and if it's a wheel
-        if let Ok(name) = names.get(entity) {
-            let name_str = name.as_str();
-            if name_str == "wheel-back-left" 
-                || name_str == "wheel-back-right" 
-                || name_str == "wheel-front-left" 
-                || name_str == "wheel-front-right" {
-                // Mark this entity as a wheel
-                commands.entity(entity).insert(Wheel);
-                
-                // Also mark front wheels for steering
-                if name_str == "wheel-front-left" || name_str == "wheel-front-right" {
-                    commands.entity(entity).insert(FrontWheel);
-                    println!("Found and marked front wheel: {}", name_str);
-                } else {
-                    println!("Found and marked rear wheel: {}", name_str);
-                }
-            }
-        }
-
-        // Recursively check children
-        if let Ok(child_entities) = children.get(entity) {
-            mark_wheels_recursive(commands, child_entities, children, names, existing_wheels);
-        }
-    }
+    Some(car)
 }
 
 fn spawn_track_markers(
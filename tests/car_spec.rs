@@ -0,0 +1,139 @@
+use bevy::gltf::GltfPlugin;
+use bevy::render::mesh::skinning::SkinnedMeshInverseBindposes;
+use bevy::scene::ScenePlugin;
+use bevy::state::app::StatesPlugin;
+use bevy_rapier3d::prelude::*;
+use bevy_vibes::*;
+use bevy_vibes::car::{Car, FrontWheel, WheelSuspension};
+use bevy_vibes::car_spec::{CarModelScene, CarRoster, CarSpecHandle, CarSpecPlugin, ROSTER};
+use bevy_vibes::menu::GameState;
+use std::time::Duration;
+
+/// Headless app that loads the real roster specs and GLB models
+fn roster_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, TransformPlugin, AssetPlugin::default(), StatesPlugin, ImagePlugin::default()))
+        .add_plugins((ScenePlugin, GltfPlugin::default(), AnimationPlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<SkinnedMeshInverseBindposes>()
+        // Everything a glTF scene puts on its nodes, normally registered by the render plugins
+        .register_type::<Visibility>()
+        .register_type::<InheritedVisibility>()
+        .register_type::<ViewVisibility>()
+        .register_type::<Mesh3d>()
+        .register_type::<MeshMaterial3d<StandardMaterial>>()
+        .register_type::<bevy::render::primitives::Aabb>()
+        .register_type::<bevy::render::view::visibility::VisibilityClass>()
+        .insert_state(GameState::InGame)
+        .add_plugins(CarSpecPlugin);
+    // The glTF loader is registered when the plugins finish, which only `App::run` does on its own
+    app.finish();
+    app.cleanup();
+    app.update();
+    app
+}
+
+/// Spawn a car from the roster and wait for its model's parts to be discovered
+fn build_car(app: &mut App, id: &str) -> Entity {
+    let spec = app.world().resource::<CarRoster>().spec(id).expect("not in the roster").clone();
+    let car = app.world_mut().spawn((Transform::default(), Visibility::default(), Car::default(), CarSpecHandle(spec))).id();
+    for _ in 0..500 {
+        app.update();
+        let discovered = app.world_mut().query::<&CarModelScene>().iter(app.world()).any(|model| model.instance.is_some());
+        let wheels = app.world_mut().query::<&WheelSuspension>().iter(app.world()).filter(|wheel| wheel.car == car).count();
+        if discovered && wheels > 0 {
+            app.update(); // Let the lamps spawned alongside the wheels land
+            return car;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    panic!("{id} never finished loading");
+}
+
+/// Name of every node under the car that the discovery turned into a wheel, and whether it steers
+fn wheels(app: &mut App, car: Entity) -> Vec<(String, bool)> {
+    let mut wheels: Vec<(String, bool)> = app
+        .world_mut()
+        .query::<(&WheelSuspension, &Name, Has<FrontWheel>)>()
+        .iter(app.world())
+        .filter(|(wheel, _, _)| wheel.car == car)
+        .map(|(_, name, front)| (name.to_string(), front))
+        .collect();
+    wheels.sort();
+    wheels
+}
+
+/// Lamps under the car as (name of the node they hang off, headlight)
+fn lamps(app: &mut App, car: Entity) -> Vec<(String, bool)> {
+    let mut lamps = Vec::new();
+    let mut query = app.world_mut().query::<(Entity, Has<SpotLight>, Has<PointLight>, &ChildOf)>();
+    for (entity, spot, point, child_of) in query.iter(app.world()) {
+        if !(spot || point) {
+            continue;
+        }
+        let mut ancestor = entity;
+        while let Some(parent) = app.world().get::<ChildOf>(ancestor) {
+            ancestor = parent.parent();
+        }
+        if ancestor != car {
+            continue;
+        }
+        let holder = child_of.parent();
+        let name = app.world().get::<Name>(holder).map_or("car".to_string(), |name| name.to_string());
+        lamps.push((name, spot));
+    }
+    lamps.sort();
+    lamps
+}
+
+#[test]
+fn the_roster_has_more_than_one_model() {
+    assert!(ROSTER.len() >= 2);
+    let app = roster_app();
+    let roster = app.world().resource::<CarRoster>();
+    assert_eq!(roster.next_id(ROSTER[0]), Some(ROSTER[1]));
+}
+
+#[test]
+fn parts_are_found_by_node_name_and_by_gltf_extras() {
+    let mut app = roster_app();
+    let sedan = build_car(&mut app, "sedan-sports");
+    let hatch = build_car(&mut app, "hatch-rally");
+
+    // The sedan's wheels are mapped by name in its spec, its lamps and collider come from the spec
+    let front = |name: &str| (name.to_string(), true);
+    let rear = |name: &str| (name.to_string(), false);
+    assert_eq!(
+        wheels(&mut app, sedan),
+        vec![rear("wheel-back-left"), rear("wheel-back-right"), front("wheel-front-left"), front("wheel-front-right")]
+    );
+    let sedan_lamps = lamps(&mut app, sedan);
+    assert_eq!(sedan_lamps.len(), 6);
+    assert!(sedan_lamps.iter().all(|(holder, _)| holder == "car"));
+    assert_eq!(sedan_lamps.iter().filter(|(_, headlight)| *headlight).count(), 2);
+
+    // The hatch tags every part with glTF extras - wheels, lamps on its own nodes, and the hull collider
+    assert_eq!(wheels(&mut app, hatch), vec![front("tyre-fl"), front("tyre-fr"), rear("tyre-rl"), rear("tyre-rr")]);
+    let holders: Vec<String> = lamps(&mut app, hatch).into_iter().map(|(holder, _)| holder).collect();
+    assert_eq!(
+        holders,
+        ["lamp-brake-l", "lamp-brake-r", "lamp-head-l", "lamp-head-r", "lamp-reverse-l", "lamp-reverse-r"].map(String::from)
+    );
+
+    let mut models = app.world_mut().query::<(&CarModelScene, &ChildOf)>();
+    let hints: Vec<(Entity, bool)> = models.iter(app.world()).map(|(model, parent)| (parent.parent(), model.collider_hint.is_some())).collect();
+    assert!(hints.contains(&(sedan, false)));
+    assert!(hints.contains(&(hatch, true)));
+
+    // The hull node's scale gives the collider's half extents, placed where the node sits on the car
+    let collider = app.world().get::<Collider>(hatch).unwrap();
+    let shapes = collider.as_compound().unwrap();
+    let (position, _, shape) = shapes.shapes().next().unwrap();
+    let ColliderView::Cuboid(cuboid) = shape else {
+        panic!("hull collider isn't a box");
+    };
+    let half_extents = cuboid.half_extents();
+    assert!(half_extents.distance(Vec3::new(0.52, 0.4, 1.02)) < 1e-3, "half extents {half_extents}");
+    assert!(position.distance(Vec3::new(0.0, -0.15, 0.0)) < 1e-3, "collider at {position}");
+}
@@ -0,0 +1,72 @@
+// Each test binary only uses some of these
+#![allow(dead_code)]
+
+use bevy_rapier3d::prelude::*;
+use bevy_vibes::*;
+use bevy_vibes::lap_timing::LapTimer;
+use bevy_vibes::track_path::{ActiveTrackPath, PathPoint, TrackPath};
+use std::f32::consts::TAU;
+
+/// Speed the scripted car is carried round the circuit at (m/s)
+pub const SPEED: f32 = 30.0;
+
+/// Where the scripted car is along the path, and a stretch it swerves wide of the road to skip a gate
+#[derive(Resource)]
+pub struct Route {
+    pub distance: f32,
+    pub shortcut: Option<(f32, f32)>,
+}
+
+impl Route {
+    pub fn starting_at(distance: f32) -> Self {
+        Self { distance, shortcut: None }
+    }
+}
+
+/// 40 m circle with the start line at point 0 and checkpoints a third and two thirds of the way round
+pub fn circuit() -> TrackPath {
+    let points = (0..24)
+        .map(|index| {
+            let angle = index as f32 / 24.0 * TAU;
+            PathPoint {
+                position: [40.0 * angle.sin(), 0.11, 40.0 * angle.cos()],
+                target_speed: SPEED,
+                width: 8.0,
+                banking: 0.0,
+            }
+        })
+        .collect();
+    let mut path = TrackPath::new("circuit", points, true, 1.0);
+    path.checkpoints = vec![8, 16];
+    path
+}
+
+/// Make the circuit the active track path and give the car a lap timer. Add `follow_route` to carry it round
+pub fn add_circuit(app: &mut App, car: Entity, route: Route) -> TrackPath {
+    let path = circuit();
+    app.init_asset::<TrackPath>().insert_resource(route);
+    let handle = app.world_mut().resource_mut::<Assets<TrackPath>>().add(path.clone());
+    app.insert_resource(ActiveTrackPath(handle));
+    app.world_mut().entity_mut(car).insert(LapTimer::default());
+    path
+}
+
+// Carry the car round at a steady speed - set after the car model runs so only this decides where it goes
+pub fn follow_route(
+    time: Res<Time>,
+    mut route: ResMut<Route>,
+    active_path: Res<ActiveTrackPath>,
+    paths: Res<Assets<TrackPath>>,
+    mut car_query: Query<(&mut Transform, &mut Velocity), With<LapTimer>>,
+) {
+    let path = paths.get(&active_path.0).unwrap();
+    route.distance += SPEED * time.delta_secs();
+    let sample = path.sample(route.distance);
+    let wide = route.shortcut.is_some_and(|(from, to)| (from..to).contains(&route.distance));
+    let position = sample.position + sample.right() * if wide { 15.0 } else { 0.0 } + Vec3::Y * 0.6;
+
+    for (mut transform, mut velocity) in car_query.iter_mut() {
+        *transform = Transform::from_translation(position).looking_to(sample.direction, Vec3::Y);
+        *velocity = Velocity::linear(sample.direction * SPEED);
+    }
+}
@@ -0,0 +1,93 @@
+pub mod circuit;
+
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier3d::prelude::*;
+use bevy_vibes::*;
+use bevy_vibes::assists::DriverAssists;
+use bevy_vibes::car::{Car, CarPlugin, FrontWheel, Wheel, WheelSuspension};
+use bevy_vibes::input::{CarInput, CarInputPlugin, CarInputSet, InputSource};
+use bevy_vibes::menu::GameState;
+use bevy_vibes::simulation::{PhysicsInterpolation, SimulationPlugin};
+use std::time::Duration;
+
+/// Simulation ticks run so far, which is all the scripted driver looks at
+#[derive(Resource, Default)]
+pub struct Tick(pub usize);
+
+// Pull away, turn in, then brake - indexed by tick so every frame rate sees the same inputs
+fn scripted_driver(tick: Res<Tick>, mut car_query: Query<&mut CarInput>) {
+    for mut input in car_query.iter_mut() {
+        *input = match tick.0 {
+            0..60 => CarInput::default(),
+            60..240 => CarInput { throttle: 1.0, ..default() },
+            240..330 => CarInput { throttle: 1.0, steer: 0.6, ..default() },
+            _ => CarInput { brake: 1.0, steer: -0.3, ..default() },
+        };
+    }
+}
+
+fn count_ticks(mut tick: ResMut<Tick>) {
+    tick.0 += 1;
+}
+
+/// Headless app with one scripted car on a flat floor, rendering at `fps`
+pub fn driving_app(fps: f64) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, TransformPlugin, AssetPlugin::default(), StatesPlugin, bevy::input::InputPlugin))
+        .init_asset::<Mesh>()
+        .add_plugins(bevy::scene::ScenePlugin)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1.0 / fps)))
+        .insert_state(GameState::InGame)
+        .add_plugins((SimulationPlugin, CarInputPlugin, CarPlugin))
+        .init_resource::<Tick>()
+        .add_systems(FixedUpdate, scripted_driver.in_set(CarInputSet))
+        .add_systems(FixedLast, count_ticks);
+
+    app.world_mut().spawn((Transform::default(), RigidBody::Fixed, Collider::cuboid(500.0, 0.1, 500.0)));
+    let car = app
+        .world_mut()
+        .spawn((
+            Transform::from_xyz(0.0, 0.7, 0.0),
+            Car::default(),
+            CarInput::default(),
+            InputSource::Replay,
+            DriverAssists::default(),
+            RigidBody::Dynamic,
+            Collider::cuboid(0.95, 0.45, 2.4),
+            AdditionalMassProperties::MassProperties(MassProperties {
+                local_center_of_mass: Vec3::new(0.0, -0.45, 0.0),
+                mass: 800.0,
+                principal_inertia_local_frame: Quat::IDENTITY,
+                principal_inertia: Vec3::new(1700.0, 1800.0, 400.0),
+            }),
+        ))
+        .insert((
+            ReadMassProperties::default(),
+            ExternalForce::default(),
+            ExternalImpulse::default(),
+            Velocity::default(),
+            PhysicsInterpolation::default(),
+        ))
+        .id();
+    for (x, z, front) in [(0.3, 0.65, false), (-0.3, 0.65, false), (0.3, -0.67, true), (-0.3, -0.67, true)] {
+        let anchor = Vec3::new(x, -0.4, z);
+        let mut wheel = app.world_mut().spawn((
+            Transform::from_translation(anchor),
+            Wheel,
+            WheelSuspension::new(car, anchor, Transform::from_translation(anchor), Transform::IDENTITY),
+            ChildOf(car),
+        ));
+        if front {
+            wheel.insert(FrontWheel);
+        }
+    }
+    (app, car)
+}
+
+/// Step the app until the simulation has run `ticks` ticks
+pub fn run_ticks(app: &mut App, ticks: usize) {
+    while app.world().resource::<Tick>().0 < ticks {
+        app.update();
+    }
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use bevy_vibes::*;
+use bevy_vibes::simulation::PhysicsInterpolation;
+use common::{driving_app, run_ticks};
+
+const TICKS: usize = 420;
+
+/// Car pose at the end of every tick
+#[derive(Resource, Default)]
+struct Trajectory(Vec<(Vec3, Quat)>);
+
+fn record_trajectory(mut trajectory: ResMut<Trajectory>, car_query: Query<&PhysicsInterpolation>) {
+    for interpolation in car_query.iter() {
+        if let Some(current) = interpolation.current {
+            trajectory.0.push((current.translation, current.rotation));
+        }
+    }
+}
+
+fn drive_at(fps: f64) -> Vec<(Vec3, Quat)> {
+    let (mut app, _) = driving_app(fps);
+    app.init_resource::<Trajectory>()
+        .add_systems(FixedPostUpdate, record_trajectory);
+
+    run_ticks(&mut app, TICKS);
+    let mut trajectory = app.world_mut().remove_resource::<Trajectory>().unwrap().0;
+    trajectory.truncate(TICKS);
+    trajectory
+}
+
+#[test]
+fn trajectory_does_not_depend_on_frame_rate() {
+    let reference = drive_at(60.0);
+    assert_eq!(reference.len(), TICKS);
+
+    // The car should actually have gone somewhere, or matching trajectories prove nothing
+    let (end, _) = reference[TICKS - 1];
+    assert!(end.xz().length() > 30.0, "car only reached {end}");
+
+    for fps in [30.0, 144.0] {
+        let trajectory = drive_at(fps);
+        for (tick, (expected, actual)) in reference.iter().zip(&trajectory).enumerate() {
+            assert_eq!(expected, actual, "diverged at tick {tick} when rendering at {fps} FPS");
+        }
+    }
+}
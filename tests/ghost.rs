@@ -0,0 +1,102 @@
+mod common;
+
+use bevy_rapier3d::prelude::*;
+use bevy_vibes::*;
+use bevy_vibes::car::CameraTarget;
+use bevy_vibes::car_spec::{CarRoster, CarSpec};
+use bevy_vibes::ghost::*;
+use bevy_vibes::lap_timing::LapTimingPlugin;
+use bevy_vibes::menu::GameSettings;
+use bevy_vibes::simulation::CarSimulationSet;
+use bevy_vibes::time_trial::PersonalBestSet;
+use bevy_vibes::track_path::TrackPath;
+use common::circuit::{add_circuit, follow_route, Route, SPEED};
+use common::{driving_app, run_ticks};
+use std::path::{Path, PathBuf};
+
+fn temp_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("bevy_vibes_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    directory
+}
+
+fn pose(x: f32, yaw: f32) -> GhostPose {
+    Transform::from_xyz(x, 0.0, 0.0).with_rotation(Quat::from_rotation_y(yaw)).into()
+}
+
+#[test]
+fn ghost_laps_blend_between_ticks_and_round_trip_through_a_file() {
+    let lap = GhostLap {
+        track: "Asphalt Loop".into(),
+        car: "sedan-sports".into(),
+        time: 0.2,
+        tick: 0.1,
+        wheels: vec!["wheel-front-left".into()],
+        frames: (0..3)
+            .map(|index| GhostFrame { body: pose(index as f32, index as f32 * 0.2), wheels: vec![pose(0.0, index as f32)] })
+            .collect(),
+    };
+
+    let (body, wheels) = lap.sample(0.15).unwrap();
+    assert!((body.translation.x - 1.5).abs() < 1e-4);
+    assert!(body.rotation.angle_between(Quat::from_rotation_y(0.3)) < 1e-4);
+    assert!(wheels[0].rotation.angle_between(Quat::from_rotation_y(1.5)) < 1e-4);
+    assert_eq!(lap.sample(0.2).unwrap().0.translation.x, 2.0);
+    assert!(lap.sample(0.31).is_none());
+
+    let directory = temp_directory("ghost_file");
+    let path = ghost_path(&directory, &lap.track, &lap.car);
+    assert_eq!(path.file_name().unwrap(), "asphalt-loop_sedan-sports.ghost.ron");
+    lap.save(&path).unwrap();
+    assert_eq!(GhostLap::load(&path).unwrap(), Some(lap));
+    assert_eq!(GhostLap::load(&directory.join("missing.ghost.ron")).unwrap(), None);
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+fn ghost_app(directory: &Path) -> (App, Entity, TrackPath) {
+    let (mut app, car) = driving_app(60.0);
+    let path = add_circuit(&mut app, car, Route::starting_at(-10.0));
+    app.init_asset::<CarSpec>()
+        .init_asset::<StandardMaterial>()
+        .insert_resource(CarRoster { cars: Vec::new() })
+        .init_resource::<GameSettings>()
+        .add_event::<PersonalBestSet>()
+        .add_plugins((LapTimingPlugin, GhostPlugin))
+        .insert_resource(GhostConfig { directory: directory.to_path_buf(), import_path: directory.join("import.ghost.ron"), ..default() })
+        .add_systems(FixedUpdate, follow_route.after(CarSimulationSet).before(PhysicsSet::SyncBackend));
+    app.world_mut().entity_mut(car).insert(CameraTarget);
+    (app, car, path)
+}
+
+#[test]
+fn each_lap_is_recorded_and_personal_bests_are_saved() {
+    let directory = temp_directory("ghost_record");
+    let (mut app, car, path) = ghost_app(&directory);
+    let lap_ticks = (path.length() / SPEED * 60.0) as usize;
+    run_ticks(&mut app, lap_ticks + 30);
+
+    // A whole lap, one frame per tick, following the route from the line
+    let ghosts = app.world().resource::<Ghosts>();
+    let last = ghosts.last.clone().expect("no lap recorded");
+    assert_eq!(last.track, "circuit");
+    assert_eq!(last.car, GameSettings::default().car);
+    assert!((last.time - path.length() / SPEED).abs() < 2.0 / 60.0);
+    assert!(last.frames.len().abs_diff(lap_ticks) <= 2, "{} frames", last.frames.len());
+    for seconds in [1.0, 2.5, 4.0] {
+        let (body, _) = last.sample(seconds).unwrap();
+        let expected = path.sample(seconds * SPEED).position;
+        assert!(body.translation.xz().distance(expected.xz()) < 2.5, "{seconds}s: {} vs {expected}", body.translation);
+    }
+    assert!(ghosts.best.is_none());
+    assert!(ghosts.recording.frames.len() < 60);
+
+    // The time trial has judged it a personal best - the ghost is kept and saved for next time
+    app.world_mut().send_event(PersonalBestSet { car, time: last.time });
+    app.update();
+    app.update();
+    let ghosts = app.world().resource::<Ghosts>();
+    assert_eq!(ghosts.best.as_ref(), Some(&last));
+    let saved = GhostLap::load(&ghost_path(&directory, &last.track, &last.car)).unwrap();
+    assert_eq!(saved, Some(last));
+    let _ = std::fs::remove_dir_all(&directory);
+}
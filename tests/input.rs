@@ -0,0 +1,65 @@
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent, RawGamepadAxisChangedEvent, RawGamepadButtonChangedEvent, RawGamepadEvent};
+use bevy::input::InputPlugin;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier3d::prelude::ContactForceEvent;
+use bevy_vibes::*;
+use bevy_vibes::input::{CarInput, CarInputPlugin, GamepadConfig, InputSource, shape_axis};
+use bevy_vibes::menu::GameState;
+use std::time::Duration;
+
+/// Headless app with one keyboard-driven car and a gamepad plugged in, fed synthetic gamepad events
+fn gamepad_app() -> (App, Entity, Entity) {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin, InputPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1.0 / 60.0)))
+        .insert_state(GameState::InGame)
+        .add_event::<ContactForceEvent>()
+        .add_plugins(CarInputPlugin);
+
+    let car = app.world_mut().spawn((CarInput::default(), InputSource::Keyboard)).id();
+    let gamepad = app.world_mut().spawn_empty().id();
+    let connection = GamepadConnection::Connected { name: "Test Pad".into(), vendor_id: None, product_id: None };
+    app.world_mut().send_event(GamepadConnectionEvent::new(gamepad, connection));
+    app.update();
+    (app, car, gamepad)
+}
+
+/// Move the left stick and both triggers, then run long enough for a few simulation ticks
+fn set_pad(app: &mut App, gamepad: Entity, stick: f32, throttle: f32, brake: f32) -> CarInput {
+    app.world_mut().send_event(RawGamepadEvent::Axis(RawGamepadAxisChangedEvent::new(gamepad, GamepadAxis::LeftStickX, stick)));
+    for (button, value) in [(GamepadButton::RightTrigger2, throttle), (GamepadButton::LeftTrigger2, brake)] {
+        app.world_mut().send_event(RawGamepadEvent::Button(RawGamepadButtonChangedEvent::new(gamepad, button, value)));
+    }
+    for _ in 0..5 {
+        app.update();
+    }
+    *app.world_mut().query::<&CarInput>().single(app.world()).unwrap()
+}
+
+#[test]
+fn gamepad_stick_and_triggers_drive_the_car() {
+    let (mut app, car, gamepad) = gamepad_app();
+    assert!(app.world().get::<Gamepad>(gamepad).is_some(), "gamepad never connected");
+    let config = GamepadConfig::default();
+
+    // Touching the throttle hands the car to the pad, and a stick resting inside the deadzone doesn't steer
+    let input = set_pad(&mut app, gamepad, config.steer_deadzone * 0.8, 0.8, 0.0);
+    assert_eq!(app.world().get::<InputSource>(car), Some(&InputSource::Gamepad));
+    assert_eq!(input.steer, 0.0);
+    assert!((input.throttle - shape_axis(0.8, config.trigger_deadzone, 1.0)).abs() < 1e-4, "throttle {}", input.throttle);
+    assert!(input.throttle > 0.75);
+    assert_eq!(input.brake, 0.0);
+
+    // Halfway past the deadzone the curve softens the steering below a straight line
+    let halfway = config.steer_deadzone + (1.0 - config.steer_deadzone) * 0.5;
+    let input = set_pad(&mut app, gamepad, halfway, 0.0, 0.5);
+    assert!((input.steer - 0.5_f32.powf(config.steer_exponent)).abs() < 1e-4, "steer {}", input.steer);
+    assert!(input.steer < 0.5);
+    assert_eq!(input.throttle, 0.0);
+    assert!((input.brake - shape_axis(0.5, config.trigger_deadzone, 1.0)).abs() < 1e-4, "brake {}", input.brake);
+
+    // Full lock either way still reaches the end of the range
+    assert_eq!(set_pad(&mut app, gamepad, -1.0, 0.0, 0.0).steer, -1.0);
+    assert_eq!(set_pad(&mut app, gamepad, 1.0, 0.0, 0.0).steer, 1.0);
+}
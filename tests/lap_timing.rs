@@ -0,0 +1,79 @@
+mod common;
+
+use bevy_rapier3d::prelude::*;
+use bevy_vibes::*;
+use bevy_vibes::lap_timing::{CheckpointPassed, LapCompleted, LapTimer, LapTimingPlugin};
+use bevy_vibes::simulation::CarSimulationSet;
+use common::circuit::{add_circuit, circuit, follow_route, Route, SPEED};
+use common::{driving_app, run_ticks};
+
+#[derive(Resource, Default)]
+struct Log {
+    checkpoints: Vec<CheckpointPassed>,
+    laps: Vec<LapCompleted>,
+}
+
+fn log_events(mut log: ResMut<Log>, mut checkpoints: EventReader<CheckpointPassed>, mut laps: EventReader<LapCompleted>) {
+    log.checkpoints.extend(checkpoints.read().copied());
+    log.laps.extend(laps.read().cloned());
+}
+
+fn lapping_app(shortcut: Option<(f32, f32)>) -> (App, Entity, f32) {
+    let (mut app, car) = driving_app(60.0);
+    let length = add_circuit(&mut app, car, Route { distance: -10.0, shortcut }).length();
+    app.add_plugins(LapTimingPlugin)
+        .init_resource::<Log>()
+        .add_systems(FixedUpdate, follow_route.after(CarSimulationSet).before(PhysicsSet::SyncBackend))
+        .add_systems(FixedPostUpdate, log_events);
+    (app, car, length)
+}
+
+#[test]
+fn clean_laps_are_timed_through_every_checkpoint() {
+    let (mut app, car, length) = lapping_app(None);
+    let lap_ticks = (length / SPEED * 60.0) as usize;
+    run_ticks(&mut app, lap_ticks * 2 + 60);
+
+    let log = app.world().resource::<Log>();
+    let gates: Vec<usize> = log.checkpoints.iter().map(|event| event.checkpoint).collect();
+    assert_eq!(gates, [1, 2, 1, 2]);
+    assert_eq!(log.laps.len(), 2);
+
+    // Every lap takes the same distance at the same speed - to within a tick either end
+    for (number, lap) in log.laps.iter().enumerate() {
+        assert!(lap.valid);
+        assert_eq!(lap.lap, number as u32 + 1);
+        assert!((lap.time - length / SPEED).abs() < 2.0 / 60.0, "lap {} took {}", number, lap.time);
+        assert_eq!(lap.sectors.len(), 3);
+        assert!((lap.sectors.iter().sum::<f32>() - lap.time).abs() < 1e-4);
+        assert!(lap.sectors.iter().all(|&sector| (sector - lap.time / 3.0).abs() < 0.1));
+    }
+    assert!(log.laps[0].best);
+
+    let timer = app.world().get::<LapTimer>(car).unwrap();
+    assert_eq!(timer.lap, 2);
+    assert_eq!(timer.last, Some(log.laps[1].time));
+    assert_eq!(timer.best, Some(log.laps[0].time.min(log.laps[1].time)));
+    assert_eq!(timer.next_checkpoint, 1);
+}
+
+#[test]
+fn laps_that_skip_a_checkpoint_are_rejected() {
+    // Swing wide round the second checkpoint on the first lap
+    let second_gate = circuit().length() * 2.0 / 3.0;
+    let (mut app, car, length) = lapping_app(Some((second_gate - 10.0, second_gate + 10.0)));
+    let lap_ticks = (length / SPEED * 60.0) as usize;
+    run_ticks(&mut app, lap_ticks * 2 + 60);
+
+    let log = app.world().resource::<Log>();
+    let gates: Vec<usize> = log.checkpoints.iter().map(|event| event.checkpoint).collect();
+    assert_eq!(gates, [1, 1, 2]);
+    assert_eq!(log.laps.len(), 2);
+    assert!(!log.laps[0].valid);
+    assert_eq!(log.laps[0].lap, 0);
+    assert!(log.laps[1].valid && log.laps[1].best);
+
+    let timer = app.world().get::<LapTimer>(car).unwrap();
+    assert_eq!(timer.lap, 1);
+    assert_eq!(timer.best, Some(log.laps[1].time));
+}
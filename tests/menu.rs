@@ -0,0 +1,63 @@
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent, RawGamepadButtonChangedEvent, RawGamepadEvent};
+use bevy::input::InputPlugin;
+use bevy::state::app::StatesPlugin;
+use bevy_vibes::*;
+use bevy_vibes::menu::{ExitButton, MenuPlugin, PlayButton, TimeTrialButton};
+
+/// Headless app sitting on the main menu with a gamepad plugged in
+fn menu_app() -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin, InputPlugin, MenuPlugin));
+
+    let gamepad = app.world_mut().spawn_empty().id();
+    let connection = GamepadConnection::Connected { name: "Test Pad".into(), vendor_id: None, product_id: None };
+    app.world_mut().send_event(GamepadConnectionEvent::new(gamepad, connection));
+    app.update();
+    app.update(); // Enter the main menu
+    (app, gamepad)
+}
+
+/// Press and release a pad button, returning the menu button the press activated
+fn tap(app: &mut App, gamepad: Entity, button: GamepadButton) -> Option<Entity> {
+    app.world_mut().send_event(RawGamepadEvent::Button(RawGamepadButtonChangedEvent::new(gamepad, button, 1.0)));
+    app.update();
+    let pressed = app
+        .world_mut()
+        .query::<(Entity, &Interaction)>()
+        .iter(app.world())
+        .find(|(_, interaction)| **interaction == Interaction::Pressed)
+        .map(|(entity, _)| entity);
+    app.world_mut().send_event(RawGamepadEvent::Button(RawGamepadButtonChangedEvent::new(gamepad, button, 0.0)));
+    app.update();
+    pressed
+}
+
+fn button<T: Component>(app: &mut App) -> Entity {
+    app.world_mut().query_filtered::<Entity, With<T>>().single(app.world()).unwrap()
+}
+
+#[test]
+fn the_pad_presses_the_focused_menu_button() {
+    let (mut app, gamepad) = menu_app();
+    let time_trial = button::<TimeTrialButton>(&mut app);
+
+    // The first touch only shows where the focus is
+    assert_eq!(tap(&mut app, gamepad, GamepadButton::South), None);
+
+    // One down from Play lands on Time Trial
+    assert_eq!(tap(&mut app, gamepad, GamepadButton::DPadDown), None);
+    assert_eq!(tap(&mut app, gamepad, GamepadButton::South), Some(time_trial));
+}
+
+#[test]
+fn the_menu_focus_wraps_around_both_ends() {
+    let (mut app, gamepad) = menu_app();
+    let play = button::<PlayButton>(&mut app);
+    let exit = button::<ExitButton>(&mut app);
+
+    tap(&mut app, gamepad, GamepadButton::South);
+    tap(&mut app, gamepad, GamepadButton::DPadUp);
+    assert_eq!(tap(&mut app, gamepad, GamepadButton::South), Some(exit));
+    tap(&mut app, gamepad, GamepadButton::DPadDown);
+    assert_eq!(tap(&mut app, gamepad, GamepadButton::South), Some(play));
+}
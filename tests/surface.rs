@@ -0,0 +1,36 @@
+mod common;
+
+use bevy::audio::{AudioPlugin, Decodable};
+use bevy_rapier3d::prelude::*;
+use bevy_vibes::*;
+use bevy_vibes::surface::{SurfacePlugin, SurfaceType, TireAudio, TireSound, TireSoundClip};
+use common::{driving_app, run_ticks};
+
+#[test]
+fn every_tyre_loop_is_audible_and_in_range() {
+    for sound in [TireSound::Squeal, TireSound::Rustle, TireSound::Crunch, TireSound::Hiss] {
+        let samples: Vec<f32> = TireSoundClip { sound }.decoder().take(44100).collect();
+        assert_eq!(samples.len(), 44100, "{sound:?} ran out");
+        assert!(samples.iter().all(|sample| sample.abs() <= 1.0));
+        let rms = (samples.iter().map(|sample| sample * sample).sum::<f32>() / samples.len() as f32).sqrt();
+        assert!(rms > 0.02, "{sound:?} is almost silent: {rms}");
+    }
+}
+
+#[test]
+fn a_car_on_gravel_plays_the_gravel_loop() {
+    let (mut app, car) = driving_app(60.0);
+    app.init_asset::<StandardMaterial>()
+        .add_plugins((AudioPlugin::default(), SurfacePlugin));
+    let floor = app.world_mut().query_filtered::<Entity, With<Collider>>().iter(app.world()).find(|entity| *entity != car).unwrap();
+    app.world_mut().entity_mut(floor).insert(SurfaceType::Gravel);
+
+    // Parked, nothing to hear
+    run_ticks(&mut app, 50);
+    assert_eq!(app.world_mut().query::<&TireAudio>().iter(app.world()).count(), 0);
+
+    // Rolling over the stones
+    run_ticks(&mut app, 200);
+    let emitters: Vec<_> = app.world_mut().query::<(&TireAudio, &ChildOf)>().iter(app.world()).map(|(audio, parent)| (audio.car, audio.sound, parent.parent())).collect();
+    assert_eq!(emitters, vec![(car, TireSound::Crunch, car)]);
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use bevy_vibes::telemetry::{Telemetry, TelemetryConfig, TelemetryPlugin};
+use common::{driving_app, run_ticks};
+
+#[test]
+fn headless_run_records_every_tick_into_the_ring_buffer() {
+    let (mut app, _) = driving_app(60.0);
+    app.insert_resource(TelemetryConfig { capacity: 200, ..Default::default() })
+        .add_plugins(TelemetryPlugin);
+    run_ticks(&mut app, 400);
+
+    // Only the newest ticks are kept, one sample each
+    let telemetry = app.world().resource::<Telemetry>();
+    assert_eq!(telemetry.len(), 200);
+    let ticks: Vec<u64> = telemetry.samples().map(|sample| sample.tick).collect();
+    assert!(ticks.windows(2).all(|pair| pair[1] == pair[0] + 1));
+    assert_eq!(*ticks.last().unwrap(), 399);
+
+    // The scripted driver is braking by now, and the breakdown shows it
+    let last = telemetry.samples().last().unwrap();
+    assert_eq!(last.brake, 1.0);
+    assert!(last.forces.brake > 0.0);
+    assert!(last.forces.suspension > 0.0);
+
+    let csv = telemetry.to_csv();
+    let mut lines = csv.lines();
+    let columns = lines.next().unwrap().split(',').count();
+    assert!(lines.clone().all(|line| line.split(',').count() == columns));
+    assert_eq!(lines.count(), 200);
+
+    let json: serde_json::Value = serde_json::from_str(&telemetry.to_json().unwrap()).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 200);
+    assert!(json[0]["forces"]["drive"].is_number());
+}
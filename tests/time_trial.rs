@@ -0,0 +1,125 @@
+mod common;
+
+use bevy_rapier3d::prelude::*;
+use bevy_vibes::*;
+use bevy_vibes::car::CameraTarget;
+use bevy_vibes::lap_timing::{LapTimer, LapTimingPlugin};
+use bevy_vibes::menu::{GameMode, GameSettings};
+use bevy_vibes::recovery::{CarReset, ResetReason};
+use bevy_vibes::simulation::CarSimulationSet;
+use bevy_vibes::time_trial::*;
+use common::circuit::{add_circuit, circuit, follow_route, Route, SPEED};
+use common::{driving_app, run_ticks, Tick};
+use std::path::PathBuf;
+
+const RUN_UP: f32 = 40.0;
+
+fn time_trial_app(records_path: PathBuf) -> (App, Entity, f32) {
+    let (mut app, car) = driving_app(60.0);
+    // Released from the grid `RUN_UP` metres before the line
+    let length = add_circuit(&mut app, car, Route::starting_at(-RUN_UP)).length();
+    app.insert_state(GameMode::TimeTrial)
+        .init_resource::<GameSettings>()
+        .add_event::<CarReset>()
+        .add_plugins((LapTimingPlugin, TimeTrialPlugin))
+        .insert_resource(TimeTrialConfig { laps: 2, countdown: 0.5, run_up: RUN_UP, records_path, ..default() })
+        .add_systems(FixedUpdate, follow_route
+            .after(CarSimulationSet)
+            .before(PhysicsSet::SyncBackend)
+            .run_if(in_state(TimeTrialState::Running)));
+    app.world_mut().entity_mut(car).insert(CameraTarget);
+    (app, car, length)
+}
+
+fn records_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("bevy_vibes_{}_{}.ron", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn state(app: &App) -> TimeTrialState {
+    *app.world().resource::<State<TimeTrialState>>().get()
+}
+
+fn run_until(app: &mut App, condition: impl Fn(&App) -> bool) {
+    let limit = app.world().resource::<Tick>().0 + 3000;
+    while !condition(app) {
+        assert!(app.world().resource::<Tick>().0 < limit, "gave up waiting");
+        app.update();
+    }
+}
+
+#[test]
+fn a_run_times_rolling_laps_and_saves_the_personal_best() {
+    let path = records_path("run");
+    let (mut app, car, length) = time_trial_app(path.clone());
+
+    // Held on the grid until the countdown runs out
+    run_ticks(&mut app, 20);
+    assert_eq!(state(&app), TimeTrialState::PreStart);
+    assert!(app.world().resource::<TimeTrial>().placed);
+    run_until(&mut app, |app| state(app) == TimeTrialState::Running);
+
+    // The clock starts at the line, not at the release, so the first lap is a full flying lap
+    run_until(&mut app, |app| app.world().resource::<TimeTrial>().laps.len() == 1);
+    let first = app.world().resource::<TimeTrial>().laps[0].time;
+    assert!((first - length / SPEED).abs() < 2.0 / 60.0, "first lap took {first}");
+
+    // Halfway round the second lap at the same pace, level with the best
+    let ticks = app.world().resource::<Tick>().0;
+    run_ticks(&mut app, ticks + (length / SPEED * 30.0) as usize);
+    let delta = app.world().resource::<TimeTrial>().delta.expect("no live delta");
+    assert!(delta.abs() < 0.1, "delta {delta}");
+
+    run_until(&mut app, |app| state(app) == TimeTrialState::Finished);
+    let trial = app.world().resource::<TimeTrial>();
+    assert_eq!(trial.laps.len(), 2);
+    assert!(trial.new_record);
+    assert!(!app.world().get::<LapTimer>(car).unwrap().started);
+
+    // Saved per track and car, with a trace that covers the lap
+    let car_id = app.world().resource::<GameSettings>().car.clone();
+    let saved = PersonalBests::load(&path).unwrap();
+    assert_eq!(&saved, app.world().resource::<PersonalBests>());
+    let best = saved.get("circuit", &car_id).expect("personal best not saved");
+    assert!((best.time - length / SPEED).abs() < 2.0 / 60.0);
+    assert_eq!(best.sectors.len(), 3);
+    assert!(best.trace.len() as f32 >= length / TRACE_SPACING - 2.0);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn restarting_puts_the_car_back_on_the_grid_and_keeps_a_quicker_best() {
+    // A record that can't be beaten at this pace
+    let path = records_path("restart");
+    let car_id = GameSettings::default().car;
+    let record = PersonalBest { track: "circuit".into(), car: car_id.clone(), time: 1.0, sectors: vec![], trace: vec![] };
+    PersonalBests { records: vec![record.clone()] }.save(&path).unwrap();
+    let (mut app, car, _) = time_trial_app(path.clone());
+
+    run_until(&mut app, |app| app.world().get::<LapTimer>(car).unwrap().started);
+    let before = app.world().get::<LapTimer>(car).unwrap().current;
+    app.world_mut().send_event(CarReset { car, reason: ResetReason::Manual });
+    app.update();
+    let after = app.world().get::<LapTimer>(car).unwrap().current;
+    assert!(after - before >= TimeTrialConfig::default().reset_penalty);
+    assert_eq!(app.world().resource::<TimeTrial>().penalties, TimeTrialConfig::default().reset_penalty);
+
+    run_until(&mut app, |app| app.world().resource::<TimeTrial>().laps.len() == 1);
+    assert!(!app.world().resource::<TimeTrial>().new_record);
+    assert_eq!(app.world().resource::<PersonalBests>().get("circuit", &car_id), Some(&record));
+
+    // Straight back to the countdown with a clean slate
+    app.world_mut().send_event(RestartTimeTrial);
+    run_until(&mut app, |app| state(app) == TimeTrialState::PreStart && app.world().resource::<TimeTrial>().placed);
+    app.update();
+    let trial = app.world().resource::<TimeTrial>();
+    assert!(trial.laps.is_empty());
+    assert_eq!(trial.penalties, 0.0);
+    assert!(!app.world().get::<LapTimer>(car).unwrap().started);
+
+    let grid = circuit().sample(-RUN_UP).position;
+    let position = app.world().get::<Transform>(car).unwrap().translation;
+    assert!(position.xz().distance(grid.xz()) < 0.5, "car at {position}");
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use bevy_vibes::tire::TireSpec;
+
+const LOAD: f32 = 4000.0;
+
+#[test]
+fn no_slip_means_no_force() {
+    let tire = TireSpec::default();
+    assert_eq!(tire.force(0.0, 0.0, LOAD, 1.0), Vec2::ZERO);
+
+    // Rolling freely and tracking straight gives no slip to begin with
+    assert_eq!(tire.slip_ratio(20.0, 20.0), 0.0);
+    assert_eq!(tire.slip_angle(20.0, 0.0), 0.0);
+    assert_eq!(tire.force(0.3, 0.2, 0.0, 1.0), Vec2::ZERO);
+}
+
+#[test]
+fn grip_peaks_at_the_peak_slip_and_falls_away_after() {
+    let tire = TireSpec::default();
+    assert!((tire.grip_curve(1.0) - 1.0).abs() < 1e-4);
+    for slip in [0.25, 0.5, 0.9] {
+        assert!(tire.grip_curve(slip) < tire.grip_curve(1.0), "grip {} at {slip}", tire.grip_curve(slip));
+    }
+    assert!(tire.grip_curve(1.5) < tire.grip_curve(1.0));
+    assert!(tire.grip_curve(3.0) < tire.grip_curve(1.5));
+
+    // The same holds for each direction in real slip units
+    let peak = tire.force(tire.peak_slip_ratio, 0.0, LOAD, 1.0).x;
+    assert!(tire.force(tire.peak_slip_ratio * 0.5, 0.0, LOAD, 1.0).x < peak);
+    assert!(tire.force(tire.peak_slip_ratio * 2.0, 0.0, LOAD, 1.0).x < peak);
+    let peak = tire.force(0.0, tire.peak_slip_angle, LOAD, 1.0).y.abs();
+    assert!(tire.force(0.0, tire.peak_slip_angle * 0.5, LOAD, 1.0).y.abs() < peak);
+    assert!(tire.force(0.0, tire.peak_slip_angle * 2.0, LOAD, 1.0).y.abs() < peak);
+}
+
+#[test]
+fn combined_force_stays_inside_the_friction_circle() {
+    let tire = TireSpec::default();
+    let limit = tire.peak_friction * LOAD;
+    let mut largest: f32 = 0.0;
+    for ratio in -10..=10 {
+        for angle in -10..=10 {
+            let force = tire.force(ratio as f32 * 0.05, angle as f32 * 0.05, LOAD, 1.0);
+            assert!(force.length() <= limit * 1.0001, "{force} at {ratio}, {angle}");
+            largest = largest.max(force.length());
+        }
+    }
+    assert!(largest > limit * 0.99, "never reached the limit: {largest}");
+
+    // Sliding right pushes the tyre left
+    assert!(tire.force(0.0, 0.1, LOAD, 1.0).y < 0.0);
+}
+
+#[test]
+fn force_scales_with_surface_friction() {
+    let tire = TireSpec::default();
+    let asphalt = tire.force(0.05, 0.08, LOAD, 1.0);
+    for friction in [0.3, 0.6, 1.2] {
+        let force = tire.force(0.05, 0.08, LOAD, friction);
+        assert!((force - asphalt * friction).length() < 1e-2, "{force} on {friction}");
+    }
+}
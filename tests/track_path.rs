@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use bevy_vibes::track_path::{PathPoint, TrackPath};
+use std::f32::consts::TAU;
+
+/// Anticlockwise circle seen from above, starting at +Z heading +X like the loop track
+fn circle(radius: f32, count: usize) -> TrackPath {
+    let points = (0..count)
+        .map(|index| {
+            let angle = index as f32 / count as f32 * TAU;
+            PathPoint {
+                position: [radius * angle.sin(), 0.0, radius * angle.cos()],
+                target_speed: 20.0,
+                width: 8.0,
+                banking: 0.0,
+            }
+        })
+        .collect();
+    TrackPath::new("circle", points, true, 1.0)
+}
+
+#[test]
+fn circle_queries_match_its_geometry() {
+    let path = circle(20.0, 24);
+    assert!((path.length() - TAU * 20.0).abs() < 0.5, "length {}", path.length());
+
+    // Turning left all the way round at 1 / radius
+    for distance in (0..path.length() as i32).step_by(5) {
+        let curvature = path.curvature_at(distance as f32);
+        assert!((curvature - 0.05).abs() < 0.01, "curvature {curvature} at {distance}");
+    }
+
+    // A quarter of the way round the car is on the +X side heading -Z
+    let quarter = path.sample(path.length() * 0.25);
+    assert!(quarter.position.distance(Vec3::new(20.0, 0.0, 0.0)) < 0.2);
+    assert!(quarter.direction.dot(Vec3::NEG_Z) > 0.99);
+
+    // Outside the circle is right of the path, inside is left
+    let outside = path.project(Vec3::new(22.0, 1.0, 0.0));
+    assert!((outside.distance - path.length() * 0.25).abs() < 0.5);
+    assert!((outside.lateral - 2.0).abs() < 0.1, "lateral {}", outside.lateral);
+    assert!(path.project(Vec3::new(17.0, 0.0, 0.0)).lateral < -2.9);
+
+    // Progress deltas take the short way across the start line
+    let length = path.length();
+    assert!((path.delta(length - 1.0, 1.0) - 2.0).abs() < 1e-3);
+    assert!((path.delta(1.0, length - 1.0) + 2.0).abs() < 1e-3);
+    assert!((path.sample(-1.0).distance - (length - 1.0)).abs() < 1e-3);
+}
+
+#[test]
+fn open_paths_stop_at_their_ends() {
+    let points = [[0.0, 0.0, 0.0], [0.0, 0.0, -10.0], [0.0, 0.0, -20.0]]
+        .map(|position| PathPoint { position, target_speed: 10.0, width: 6.0, banking: 0.0 })
+        .to_vec();
+    let path = TrackPath::new("straight", points, false, 1.0);
+
+    assert!((path.length() - 20.0).abs() < 1e-3);
+    assert_eq!(path.sample(50.0).position, Vec3::new(0.0, 0.0, -20.0));
+    assert_eq!(path.curvature_at(10.0), 0.0);
+    assert_eq!(path.progress(Vec3::new(1.0, 0.0, 5.0)), 0.0);
+    assert_eq!(path.delta(18.0, 2.0), -16.0);
+}